@@ -0,0 +1,439 @@
+use anyhow::{anyhow, Context};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::{fs, path::PathBuf};
+use toml_edit::Document;
+
+use crate::config::{
+    check_settings, config_files, find_vault_config, is_setting_key, is_string_setting,
+    is_trusted_setting, user_config_file, LoadedConfig, Sources, VAULT_CONFIG_FILE,
+};
+
+#[derive(Debug)]
+enum Action {
+    Show {
+        loaded: LoadedConfig,
+        json: bool,
+    },
+    Get {
+        loaded: LoadedConfig,
+        key: String,
+    },
+    Set {
+        file: PathBuf,
+        local: bool,
+        key: String,
+        value: String,
+    },
+    Path(Vec<PathBuf>),
+}
+
+/// Shows the settings templates and subcommands see, and edits config files.
+#[derive(Debug)]
+pub struct ConfigCommand {
+    action: Action,
+}
+
+impl ConfigCommand {
+    /// Takes the top level matches since `set` and `path` also work without a valid config.
+    #[tracing::instrument]
+    pub fn try_new(matches: &ArgMatches, sub_matches: &ArgMatches) -> anyhow::Result<Self> {
+        let action = match sub_matches.subcommand() {
+            Some(("get", get_matches)) => Action::Get {
+                loaded: LoadedConfig::try_new(matches)?,
+                key: get_matches
+                    .get_one::<String>("key")
+                    .cloned()
+                    .ok_or(anyhow!("Missing key"))?,
+            },
+            Some(("set", set_matches)) => {
+                let local = set_matches.get_flag("local");
+                let file = match local {
+                    true => {
+                        let cwd =
+                            std::env::current_dir().context("Could not read current directory")?;
+                        find_vault_config(&cwd).unwrap_or(cwd.join(VAULT_CONFIG_FILE))
+                    }
+                    false => user_config_file(matches)?,
+                };
+                Action::Set {
+                    file,
+                    local,
+                    key: set_matches
+                        .get_one::<String>("key")
+                        .cloned()
+                        .ok_or(anyhow!("Missing key"))?,
+                    value: set_matches
+                        .get_one::<String>("value")
+                        .cloned()
+                        .ok_or(anyhow!("Missing value"))?,
+                }
+            }
+            Some(("path", _)) => Action::Path(
+                config_files(matches)
+                    .or_else(|_| Ok::<_, anyhow::Error>(vec![user_config_file(matches)?]))?,
+            ),
+            Some(("show", show_matches)) => Action::Show {
+                loaded: LoadedConfig::try_new(matches)?,
+                json: show_matches.get_flag("json"),
+            },
+            _ => Action::Show {
+                loaded: LoadedConfig::try_new(matches)?,
+                json: false,
+            },
+        };
+
+        Ok(ConfigCommand { action })
+    }
+
+    #[tracing::instrument]
+    pub fn run(&self) -> anyhow::Result<()> {
+        match &self.action {
+            Action::Show { loaded, json } => show(loaded, *json),
+            Action::Get { loaded, key } => get(loaded, key),
+            Action::Set {
+                file,
+                local,
+                key,
+                value,
+            } => set(file, *local, key, value),
+            Action::Path(files) => {
+                for file in files {
+                    println!("{}", file.display());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    #[tracing::instrument]
+    pub fn cmd() -> Command {
+        Command::new("config")
+            .about("Show the effective configuration and edit config files")
+            .subcommand(
+                Command::new("show")
+                    .about("Print the merged configuration with where each value comes from")
+                    .arg(
+                        Arg::new("json")
+                            .long("json")
+                            .action(ArgAction::SetTrue)
+                            .help("Print the configuration and the sources as json"),
+                    ),
+            )
+            .subcommand(
+                Command::new("get").about("Print a single setting").arg(
+                    Arg::new("key")
+                        .required(true)
+                        .help("Dotted key of the setting. Ex. notes_dir or meta.author"),
+                ),
+            )
+            .subcommand(
+                Command::new("set")
+                    .about("Change a setting in config.toml, keeping its comments")
+                    .arg(
+                        Arg::new("key")
+                            .required(true)
+                            .help("Dotted key of the setting. Ex. notes_dir or meta.author"),
+                    )
+                    .arg(
+                        Arg::new("value")
+                            .required(true)
+                            .help("A TOML value, ex. true or [\"git\"], or else a string. Text settings and meta are always strings"),
+                    )
+                    .arg(
+                        Arg::new("local")
+                            .long("local")
+                            .action(ArgAction::SetTrue)
+                            .help("Change the nearest .notes-cli.toml instead"),
+                    ),
+            )
+            .subcommand(Command::new("path").about("Print the config files that are read"))
+    }
+}
+
+/// The effective configuration as a TOML table. Unset optional settings are left out.
+fn settings(loaded: &LoadedConfig) -> anyhow::Result<toml::Table> {
+    toml::Table::try_from(&loaded.config).context("Could not serialize config")
+}
+
+fn show(loaded: &LoadedConfig, json: bool) -> anyhow::Result<()> {
+    let settings = settings(loaded)?;
+    if json {
+        let sources: Sources = leaf_keys(&settings, "")
+            .into_iter()
+            .map(|key| {
+                let source = source(&loaded.sources, &key);
+                (key, source)
+            })
+            .collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "config": settings,
+                "sources": sources,
+            }))?
+        );
+        return Ok(());
+    }
+
+    print!("{}", annotated(&settings, "", &loaded.sources));
+    Ok(())
+}
+
+fn get(loaded: &LoadedConfig, key: &str) -> anyhow::Result<()> {
+    let settings = settings(loaded)?;
+    let mut value = None;
+    let mut table = Some(&settings);
+    for part in key.split('.') {
+        value = table.and_then(|t| t.get(part));
+        table = value.and_then(|v| v.as_table());
+    }
+
+    match value.ok_or(anyhow!("{} is not set", key))? {
+        toml::Value::String(s) => println!("{}", s),
+        toml::Value::Table(t) => print!("{}", annotated(t, &format!("{}.", key), &Sources::new())),
+        v => println!("{}", v),
+    }
+    Ok(())
+}
+
+/// Sets `key` in `file` without touching the rest of the file. Fails, leaving the file as it
+/// is, when the value is not valid for the setting. Settings that let config run commands are
+/// ignored in a `local` file, so they can not be set there.
+fn set(file: &PathBuf, local: bool, key: &str, value: &str) -> anyhow::Result<()> {
+    if !is_setting_key(key) {
+        return Err(anyhow!("{} is not a setting", key));
+    }
+    if local && is_trusted_setting(key) {
+        return Err(anyhow!(
+            "{} is ignored in {}, set it in config.toml without --local",
+            key,
+            VAULT_CONFIG_FILE
+        ));
+    }
+
+    let contents = match file.exists() {
+        true => fs::read_to_string(file).with_context(|| format!("Could not read {:?}", file))?,
+        false => String::new(),
+    };
+    let mut document = contents
+        .parse::<Document>()
+        .with_context(|| format!("Could not parse config file {:?}", file))?;
+
+    let parts: Vec<&str> = key.split('.').collect();
+    let (last, tables) = parts.split_last().ok_or(anyhow!("Missing key"))?;
+    let mut table = document.as_table_mut();
+    for part in tables {
+        let item = table.entry(part).or_insert_with(|| {
+            let mut table = toml_edit::Table::new();
+            table.set_implicit(true);
+            toml_edit::Item::Table(table)
+        });
+        table = item
+            .as_table_mut()
+            .ok_or(anyhow!("{} in {:?} is not a table", part, file))?;
+    }
+
+    let path: Vec<String> = parts.iter().map(|p| p.to_string()).collect();
+    let new_value = match is_string_setting(&path) {
+        true => toml_edit::Value::from(value),
+        false => value
+            .parse::<toml_edit::Value>()
+            .unwrap_or_else(|_| toml_edit::Value::from(value)),
+    };
+    match table.get_mut(last).and_then(|item| item.as_value_mut()) {
+        // Keeps comments after the old value
+        Some(old) => {
+            let decor = old.decor().clone();
+            *old = new_value;
+            *old.decor_mut() = decor;
+        }
+        None => {
+            table.insert(last, toml_edit::value(new_value));
+        }
+    }
+
+    let contents = document.to_string();
+    check_settings(&contents, file)?;
+
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Could not create {:?}", dir))?;
+    }
+    fs::write(file, contents).with_context(|| format!("Could not write {:?}", file))?;
+    Ok(())
+}
+
+/// Settings not set anywhere have their default value.
+fn source(sources: &Sources, key: &str) -> String {
+    sources
+        .get(key)
+        .cloned()
+        .unwrap_or_else(|| "default".to_string())
+}
+
+/// The dotted keys of the values in `table`, ex. `meta.author`.
+fn leaf_keys(table: &toml::Table, prefix: &str) -> Vec<String> {
+    table
+        .iter()
+        .flat_map(|(key, value)| match value {
+            toml::Value::Table(table) => leaf_keys(table, &format!("{}{}.", prefix, key)),
+            _ => vec![format!("{}{}", prefix, key)],
+        })
+        .collect()
+}
+
+/// `table` as TOML with the source of each value in a comment, ex.
+/// `notes_dir = "/notes" # /home/max/.config/notes-cli/config.toml`.
+fn annotated(table: &toml::Table, prefix: &str, sources: &Sources) -> String {
+    let mut out = String::new();
+    for (key, value) in table.iter().filter(|(_, v)| !v.is_table()) {
+        out.push_str(&format!("{} = {}", toml_key(key), value));
+        if !sources.is_empty() {
+            out.push_str(&format!(
+                " # {}",
+                source(sources, &format!("{}{}", prefix, key))
+            ));
+        }
+        out.push('\n');
+    }
+    for (key, value) in table.iter() {
+        if let toml::Value::Table(table) = value {
+            let name = format!("{}{}", prefix, toml_key(key));
+            // Like TOML, a table with only tables has no header of its own
+            if table.is_empty() || table.values().any(|v| !v.is_table()) {
+                out.push_str(&format!("\n[{}]\n", name));
+            }
+            out.push_str(&annotated(table, &format!("{}.", name), sources));
+        }
+    }
+    out
+}
+
+/// `key` as a TOML key, quoted when it is not a bare key.
+fn toml_key(key: &str) -> String {
+    match !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        true => key.to_string(),
+        false => toml::Value::String(key.to_string()).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn annotates_values_with_their_source() {
+        let table: toml::Table = toml::from_str(
+            "notes_dir = \"/notes\"\nnote_file_type = \"md\"\n[meta]\nauthor = \"Max\"\n\"my key\" = \"x\"\n",
+        )
+        .unwrap();
+        let sources = Sources::from([
+            ("notes_dir".to_string(), "/config.toml".to_string()),
+            ("meta.author".to_string(), "flag".to_string()),
+        ]);
+
+        assert_eq!(
+            "note_file_type = \"md\" # default
+notes_dir = \"/notes\" # /config.toml
+
+[meta]
+author = \"Max\" # flag
+\"my key\" = \"x\" # default
+",
+            annotated(&table, "", &sources)
+        );
+    }
+
+    #[test]
+    fn sets_values_keeping_comments() {
+        let dir = std::env::temp_dir().join(format!("notes-cli-set-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("config.toml");
+        fs::write(
+            &file,
+            "# My notes\nnotes_dir = \"/notes\" # Synced\ntemplates_dir = \"/templates\"\n",
+        )
+        .unwrap();
+
+        set(&file, false, "notes_dir", "~/notes").unwrap();
+        set(&file, false, "allow_exec", "true").unwrap();
+        set(&file, false, "vaults.work.meta.team", "core").unwrap();
+        set(&file, false, "meta.ticket", "1234").unwrap();
+        set(&file, false, "meta.draft", "true").unwrap();
+        set(&file, false, "note_file_type", "2024").unwrap();
+        assert_eq!(
+            "# My notes
+notes_dir = \"~/notes\" # Synced
+templates_dir = \"/templates\"
+allow_exec = true
+note_file_type = \"2024\"
+
+[vaults.work.meta]
+team = \"core\"
+
+[meta]
+ticket = \"1234\"
+draft = \"true\"
+",
+            fs::read_to_string(&file).unwrap()
+        );
+
+        for key in [
+            "notes_directory",
+            "foo.bar",
+            "meta.author.name",
+            "vaults.work",
+            "vaults.work.colour",
+            "vaults.work.foo.bar",
+            "vaults.work.vaults",
+            "vault",
+        ] {
+            assert_eq!(
+                format!("{} is not a setting", key),
+                set(&file, false, key, "1").unwrap_err().to_string()
+            );
+        }
+        assert!(format!(
+            "{:#}",
+            set(&file, false, "exec_timeout_ms", "soon").unwrap_err()
+        )
+        .contains("Invalid setting exec_timeout_ms"));
+        assert!(fs::read_to_string(&file)
+            .unwrap()
+            .contains("notes_dir = \"~/notes\" # Synced"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn does_not_set_trusted_settings_locally() {
+        let dir = std::env::temp_dir().join(format!("notes-cli-set-{}", uuid::Uuid::new_v4()));
+        let file = dir.join(VAULT_CONFIG_FILE);
+
+        for key in [
+            "allow_exec",
+            "subcommands.find",
+            "vaults.work.exec_allow_list",
+        ] {
+            assert_eq!(
+                format!(
+                    "{} is ignored in .notes-cli.toml, set it in config.toml without --local",
+                    key
+                ),
+                set(&file, true, key, "true").unwrap_err().to_string()
+            );
+        }
+        assert!(!file.exists());
+
+        set(&file, true, "note_file_type", "txt").unwrap();
+        assert_eq!(
+            "note_file_type = \"txt\"\n",
+            fs::read_to_string(&file).unwrap()
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,268 @@
+use anyhow::anyhow;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use serde_json::json;
+
+use crate::config::Config;
+use crate::graph::{Edge, EdgeKind, Graph};
+use crate::notes::{Note, Notes};
+
+#[derive(Debug)]
+pub struct ExportGraph {
+    config: Config,
+    format: String,
+    note: Option<String>,
+    depth: usize,
+    tag_edges: bool,
+}
+
+impl ExportGraph {
+    #[tracing::instrument]
+    pub fn try_new(config: &Config, sub_matches: &ArgMatches) -> anyhow::Result<ExportGraph> {
+        Ok(ExportGraph {
+            config: config.clone(),
+            format: sub_matches
+                .get_one::<String>("format")
+                .cloned()
+                .unwrap_or_else(|| "dot".to_string()),
+            note: sub_matches.get_one::<String>("note").cloned(),
+            depth: sub_matches.get_one::<usize>("depth").copied().unwrap_or(1),
+            tag_edges: sub_matches.get_flag("tag-edges"),
+        })
+    }
+
+    #[tracing::instrument]
+    pub fn run(&self) -> anyhow::Result<()> {
+        let notes = Notes::load(&self.config)?;
+        let mut graph = Graph::new(&notes, self.tag_edges);
+
+        if let Some(name) = &self.note {
+            let root = notes
+                .resolve(name)
+                .and_then(|n| notes.index_of(n))
+                .ok_or(anyhow!("Could not find note {}", name))?;
+            graph = graph.neighbourhood(root, self.depth);
+        }
+
+        let output = match self.format.as_str() {
+            "json" => to_json(&graph)?,
+            "mermaid" => to_mermaid(&graph),
+            "graphml" => to_graphml(&graph),
+            _ => to_dot(&graph),
+        };
+        println!("{}", output);
+
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    pub fn cmd() -> Command {
+        Command::new("graph")
+            .about("Export the links between notes as a graph")
+            .arg(
+                Arg::new("format")
+                    .short('f')
+                    .long("format")
+                    .value_parser(["dot", "json", "mermaid", "graphml"])
+                    .default_value("dot")
+                    .help("Output format"),
+            )
+            .arg(
+                Arg::new("note")
+                    .long("note")
+                    .value_name("NAME")
+                    .help("Only export the notes around this note. Ex. --note roadmap"),
+            )
+            .arg(
+                Arg::new("depth")
+                    .short('d')
+                    .long("depth")
+                    .value_name("N")
+                    .value_parser(clap::value_parser!(usize))
+                    .default_value("1")
+                    .help("Number of links to follow from --note"),
+            )
+            .arg(
+                Arg::new("tag-edges")
+                    .long("tag-edges")
+                    .action(ArgAction::SetTrue)
+                    .help("Add edges between notes that share a tag"),
+            )
+    }
+}
+
+fn node<'a>(graph: &Graph<'a>, index: usize) -> &'a Note {
+    graph
+        .notes
+        .get(index)
+        .expect("Graph nodes are indexes into notes")
+}
+
+fn escape(s: &str, replacements: &[(&str, &str)]) -> String {
+    replacements
+        .iter()
+        .fold(s.to_string(), |s, (from, to)| s.replace(from, to))
+}
+
+fn dot_escape(s: &str) -> String {
+    escape(s, &[("\\", "\\\\"), ("\"", "\\\"")])
+}
+
+fn xml_escape(s: &str) -> String {
+    escape(
+        s,
+        &[
+            ("&", "&amp;"),
+            ("<", "&lt;"),
+            (">", "&gt;"),
+            ("\"", "&quot;"),
+        ],
+    )
+}
+
+fn edge_label(edge: &Edge) -> String {
+    match edge.kind {
+        EdgeKind::Tag => format!("#{}", edge.tag.as_deref().unwrap_or_default()),
+        kind => kind.as_str().to_string(),
+    }
+}
+
+pub fn to_dot(graph: &Graph) -> String {
+    let mut out = vec!["digraph notes {".to_string()];
+
+    for i in &graph.nodes {
+        let note = node(graph, *i);
+        out.push(format!(
+            "  \"{}\" [label=\"{}\", tags=\"{}\"];",
+            dot_escape(&note.name),
+            dot_escape(&note.title),
+            dot_escape(&note.tags.join(","))
+        ));
+    }
+
+    for edge in &graph.edges {
+        let attributes = match edge.kind {
+            EdgeKind::Link => String::new(),
+            EdgeKind::Embed => " [style=dashed, label=\"embed\"]".to_string(),
+            EdgeKind::Tag => format!(
+                " [dir=none, style=dotted, label=\"{}\"]",
+                dot_escape(&edge_label(edge))
+            ),
+        };
+        out.push(format!(
+            "  \"{}\" -> \"{}\"{};",
+            dot_escape(&node(graph, edge.source).name),
+            dot_escape(&node(graph, edge.target).name),
+            attributes
+        ));
+    }
+
+    out.push("}".to_string());
+    out.join("\n")
+}
+
+pub fn to_json(graph: &Graph) -> anyhow::Result<String> {
+    let nodes: Vec<_> = graph
+        .nodes
+        .iter()
+        .map(|i| {
+            let note = node(graph, *i);
+            json!({
+                "id": note.name,
+                "title": note.title,
+                "tags": note.tags,
+                "path": note.path,
+            })
+        })
+        .collect();
+    let edges: Vec<_> = graph
+        .edges
+        .iter()
+        .map(|e| {
+            let mut edge = json!({
+                "source": node(graph, e.source).name,
+                "target": node(graph, e.target).name,
+                "kind": e.kind,
+            });
+            if let Some(tag) = &e.tag {
+                edge["tag"] = json!(tag);
+            }
+            edge
+        })
+        .collect();
+
+    Ok(serde_json::to_string_pretty(
+        &json!({ "nodes": nodes, "edges": edges }),
+    )?)
+}
+
+pub fn to_mermaid(graph: &Graph) -> String {
+    let mut out = vec!["graph LR".to_string()];
+
+    for i in &graph.nodes {
+        out.push(format!(
+            "  n{}[\"{}\"]",
+            i,
+            escape(&node(graph, *i).title, &[("\"", "#quot;")])
+        ));
+    }
+
+    for edge in &graph.edges {
+        let arrow = match edge.kind {
+            EdgeKind::Link => "-->".to_string(),
+            EdgeKind::Embed => "-.->|embed|".to_string(),
+            EdgeKind::Tag => format!("---|{}|", edge_label(edge)),
+        };
+        out.push(format!("  n{} {} n{}", edge.source, arrow, edge.target));
+    }
+
+    out.join("\n")
+}
+
+pub fn to_graphml(graph: &Graph) -> String {
+    let mut out = vec![
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string(),
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#.to_string(),
+        r#"  <key id="title" for="node" attr.name="title" attr.type="string"/>"#.to_string(),
+        r#"  <key id="tags" for="node" attr.name="tags" attr.type="string"/>"#.to_string(),
+        r#"  <key id="kind" for="edge" attr.name="kind" attr.type="string"/>"#.to_string(),
+        r#"  <key id="tag" for="edge" attr.name="tag" attr.type="string"/>"#.to_string(),
+        r#"  <graph id="notes" edgedefault="directed">"#.to_string(),
+    ];
+
+    for i in &graph.nodes {
+        let note = node(graph, *i);
+        out.push(format!("    <node id=\"{}\">", xml_escape(&note.name)));
+        out.push(format!(
+            "      <data key=\"title\">{}</data>",
+            xml_escape(&note.title)
+        ));
+        out.push(format!(
+            "      <data key=\"tags\">{}</data>",
+            xml_escape(&note.tags.join(","))
+        ));
+        out.push("    </node>".to_string());
+    }
+
+    for edge in &graph.edges {
+        out.push(format!(
+            "    <edge source=\"{}\" target=\"{}\">",
+            xml_escape(&node(graph, edge.source).name),
+            xml_escape(&node(graph, edge.target).name)
+        ));
+        out.push(format!(
+            "      <data key=\"kind\">{}</data>",
+            edge.kind.as_str()
+        ));
+        if let Some(tag) = &edge.tag {
+            out.push(format!(
+                "      <data key=\"tag\">{}</data>",
+                xml_escape(tag)
+            ));
+        }
+        out.push("    </edge>".to_string());
+    }
+
+    out.push("  </graph>".to_string());
+    out.push("</graphml>".to_string());
+    out.join("\n")
+}
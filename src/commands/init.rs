@@ -0,0 +1,257 @@
+use anyhow::{anyhow, Context};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use super::templates::export_starters;
+use crate::config::expand_path;
+
+static CONFIG_FILE: &str = "config.toml";
+
+/// Creates `config.toml`, `notes_dir` and `templates_dir`. Values not given as flags are asked
+/// for, unless `--yes` is passed.
+#[derive(Debug)]
+pub struct Init {
+    config_dir: PathBuf,
+    notes_dir: Option<PathBuf>,
+    templates_dir: Option<PathBuf>,
+    starters: Option<bool>,
+    force: bool,
+}
+
+impl Init {
+    #[tracing::instrument]
+    pub fn try_new(config_dir: PathBuf, sub_matches: &ArgMatches) -> anyhow::Result<Init> {
+        let defaults = sub_matches.get_flag("yes");
+
+        Ok(Init {
+            notes_dir: sub_matches
+                .get_one::<String>("notes-dir")
+                .map(PathBuf::from)
+                .or(defaults.then(default_notes_dir).transpose()?),
+            templates_dir: sub_matches
+                .get_one::<String>("templates-dir")
+                .map(PathBuf::from)
+                .or(defaults.then(|| config_dir.join("templates"))),
+            starters: match sub_matches.get_flag("starters") {
+                true => Some(true),
+                false => sub_matches
+                    .get_flag("no-starters")
+                    .then_some(false)
+                    .or(defaults.then_some(true)),
+            },
+            force: sub_matches.get_flag("force"),
+            config_dir,
+        })
+    }
+
+    #[tracing::instrument]
+    pub fn run(&self) -> anyhow::Result<()> {
+        let stdin = io::stdin();
+        let mut input = stdin.lock();
+
+        let notes_dir = match &self.notes_dir {
+            Some(dir) => dir.clone(),
+            None => PathBuf::from(prompt(
+                &mut input,
+                "Notes directory",
+                &default_notes_dir()?.to_string_lossy(),
+            )?),
+        };
+        let templates_dir = match &self.templates_dir {
+            Some(dir) => dir.clone(),
+            None => PathBuf::from(prompt(
+                &mut input,
+                "Templates directory",
+                &self.config_dir.join("templates").to_string_lossy(),
+            )?),
+        };
+        let starters = match self.starters {
+            Some(starters) => starters,
+            None => {
+                let answer = prompt(&mut input, "Add starter templates? [Y/n]", "y")?;
+                !answer.to_lowercase().starts_with('n')
+            }
+        };
+
+        // Written as absolute paths since config.toml paths are relative to config.toml
+        let cwd = std::env::current_dir().context("Could not read current directory")?;
+        let notes_dir = expand_path(&notes_dir.to_string_lossy(), &cwd)?;
+        let templates_dir = expand_path(&templates_dir.to_string_lossy(), &cwd)?;
+
+        let config_file = self.config_dir.join(CONFIG_FILE);
+        if config_file.exists() && !self.force {
+            println!(
+                "Skipped {:?}, it already exists. Use --force to overwrite",
+                config_file
+            );
+        } else {
+            fs::create_dir_all(&self.config_dir).with_context(|| {
+                format!("Could not create config directory {:?}", self.config_dir)
+            })?;
+            fs::write(&config_file, config_toml(&notes_dir, &templates_dir)?)
+                .with_context(|| format!("Could not write config file {:?}", config_file))?;
+            println!("{}", config_file.display());
+        }
+
+        for dir in [&notes_dir, &templates_dir] {
+            if !dir.exists() {
+                fs::create_dir_all(dir)
+                    .with_context(|| format!("Could not create directory {:?}", dir))?;
+                println!("{}", dir.display());
+            }
+        }
+
+        if starters {
+            export_starters(&templates_dir, &[], self.force)?;
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    pub fn cmd() -> Command {
+        Command::new("init")
+            .about("Create config.toml, the notes directory and the templates directory")
+            .arg(
+                Arg::new("notes-dir")
+                    .long("notes-dir")
+                    .value_name("DIR")
+                    .help("Where notes are stored. Default is ~/notes"),
+            )
+            .arg(
+                Arg::new("templates-dir")
+                    .long("templates-dir")
+                    .value_name("DIR")
+                    .help(
+                        "Where templates are stored. Default is templates in the config directory",
+                    ),
+            )
+            .arg(
+                Arg::new("starters")
+                    .long("starters")
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with("no-starters")
+                    .help("Copy the built-in starter templates into the templates directory"),
+            )
+            .arg(
+                Arg::new("no-starters")
+                    .long("no-starters")
+                    .action(ArgAction::SetTrue)
+                    .help("Do not copy the built-in starter templates"),
+            )
+            .arg(
+                Arg::new("yes")
+                    .short('y')
+                    .long("yes")
+                    .action(ArgAction::SetTrue)
+                    .help("Use the defaults instead of asking for values not given as flags"),
+            )
+            .arg(
+                Arg::new("force")
+                    .long("force")
+                    .action(ArgAction::SetTrue)
+                    .help("Overwrite config.toml and starter templates that already exist"),
+            )
+    }
+}
+
+fn default_notes_dir() -> anyhow::Result<PathBuf> {
+    std::env::var_os("HOME")
+        .map(|home| Path::new(&home).join("notes"))
+        .ok_or(anyhow!(
+            "Could not find env variable HOME. Pass --notes-dir"
+        ))
+}
+
+/// Asks for a value on stdout, ex. `Notes directory [/home/max/notes]: `. An empty answer or
+/// the end of input means `default`.
+fn prompt(input: &mut impl BufRead, question: &str, default: &str) -> anyhow::Result<String> {
+    print!("{} [{}]: ", question, default);
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    let answer = answer.trim();
+
+    Ok(match answer.is_empty() {
+        true => default.to_string(),
+        false => answer.to_string(),
+    })
+}
+
+/// `config.toml` with the given directories and every other setting commented out with its
+/// default.
+fn config_toml(notes_dir: &Path, templates_dir: &Path) -> anyhow::Result<String> {
+    let path = |p: &Path| -> anyhow::Result<String> {
+        Ok(toml::Value::String(
+            p.to_str()
+                .ok_or(anyhow!("{:?} is not valid UTF-8", p))?
+                .to_string(),
+        )
+        .to_string())
+    };
+
+    Ok(format!(
+        r#"notes_dir = {}
+templates_dir = {}
+#team_templates_dir = "~/team-notes/templates" # Used when neither .templates in notes_dir nor templates_dir has a template
+#editor = "vim" # Defaults to $EDITOR env variable
+#note_file_type = "md"
+#timezone = "local" # local, UTC or an offset like +01:00
+#strict_templates = false # Fail rendering when a template uses a missing variable
+#allow_exec = false # Lets templates run commands with {{{{exec "git config user.name"}}}}
+#exec_allow_list = ["git"] # Programs {{{{exec}}}} may run
+#exec_timeout_ms = 5000
+#orphans_exclude = ["daily"] # Folders left out of `orphans` report
+
+# Subcommands are run with sh -c and rendered as templates with `config` and `args`.
+# Ex. `notes-cli find` opens a note picked with fzf
+[subcommands]
+#find = "cd {{{{config.notes_dir}}}} && $EDITOR $(fzf {{{{args}}}})"
+
+# Available in templates as {{{{config.meta.author}}}}. Add more with -m/--meta-data KEY:VALUE
+[meta]
+#author = "Your Name"
+"#,
+        path(notes_dir)?,
+        path(templates_dir)?
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn config_toml_is_a_valid_config() {
+        let config: Config = toml::from_str(
+            &config_toml(
+                Path::new("/home/max/notes"),
+                Path::new("/home/max/templates"),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(PathBuf::from("/home/max/notes"), config.notes_dir);
+        assert_eq!(PathBuf::from("/home/max/templates"), config.templates_dir);
+        assert!(config.subcommands.is_empty());
+    }
+
+    #[test]
+    fn prompt_falls_back_to_default() {
+        let mut input = "\n/tmp/notes\n".as_bytes();
+
+        assert_eq!("~/notes", prompt(&mut input, "Notes", "~/notes").unwrap());
+        assert_eq!(
+            "/tmp/notes",
+            prompt(&mut input, "Notes", "~/notes").unwrap()
+        );
+        assert_eq!("~/notes", prompt(&mut input, "Notes", "~/notes").unwrap());
+    }
+}
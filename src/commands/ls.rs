@@ -20,7 +20,7 @@ impl ListNotes {
 
     #[tracing::instrument]
     pub fn run(&self) {
-        std::process::Command::new("ls")
+        let _ = std::process::Command::new("ls")
             .arg("-1")
             .arg(&self.config.notes_dir)
             .exec();
@@ -1,3 +1,9 @@
+pub mod config;
+pub mod graph;
+pub mod init;
 pub mod ls;
 pub mod new_note;
+pub mod orphans;
 pub mod subcommand;
+pub mod templates;
+pub mod vaults;
@@ -1,20 +1,27 @@
 use anyhow::{anyhow, Context};
 use clap::{Arg, ArgAction, ArgMatches, Command};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fs::File, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
 use time::OffsetDateTime;
 
 use crate::config::Config;
-use crate::templating::{get_templates, EMPTY_TEMPLATE_NAME};
+use crate::natural_date::parse_natural_date;
+use crate::templating::{get_templates, Now, EMPTY_TEMPLATE_NAME};
 use crate::utils::{parse_metadata, parse_metadata_json};
 
+static NAME_TEMPLATE_NAME: &str = "name-template";
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NewNote {
     pub config: Config,
     pub template: String,
     pub name: String,
     pub name_template: String,
-    pub now: String,
+    pub now: Now,
     pub meta: HashMap<String, String>,
 }
 
@@ -40,22 +47,37 @@ impl NewNote {
             .unwrap_or(&"{{name}}".to_string())
             .to_string();
 
+        let mut now = OffsetDateTime::now_utc().to_offset(config.utc_offset()?);
+        if let Some(date) = sub_matches.get_one::<String>("date") {
+            now = parse_natural_date(date, now)?;
+        }
+
+        let mut config = config.clone();
+        if sub_matches.get_flag("strict") {
+            config.strict_templates = true;
+        }
+
         Ok(NewNote {
-            config: config.clone(),
+            meta: meta.into_iter().chain(config.meta.clone()).collect(),
+            config,
             template,
             name,
             name_template,
-            now: OffsetDateTime::now_utc().to_string(),
-            meta: meta.into_iter().chain(config.meta.clone()).collect(),
+            now: now.into(),
         })
     }
 
     #[tracing::instrument]
     fn get_file_name(&self) -> anyhow::Result<PathBuf> {
         let data = serde_json::to_value(self)?;
-        let mut file = get_templates(&self.config)?
-            .render_template(&self.name_template, &data)
-            .map(PathBuf::from)?;
+        let mut templates = get_templates(&self.config)?;
+        templates
+            .register_template_string(NAME_TEMPLATE_NAME, &self.name_template)
+            .with_context(|| format!("Could not parse name template {}", self.name_template))?;
+        let mut file = templates
+            .render(NAME_TEMPLATE_NAME, &data)
+            .map(PathBuf::from)
+            .with_context(|| format!("Could not render name template {}", self.name_template))?;
         file.set_extension(&self.config.note_file_type);
 
         Ok(file)
@@ -63,22 +85,31 @@ impl NewNote {
 
     #[tracing::instrument]
     pub fn write(self) -> anyhow::Result<PathBuf> {
-        let output_file_path = PathBuf::from(&self.config.notes_dir).join(self.get_file_name()?);
+        let file_name = self.get_file_name()?;
+        let output_file_path = PathBuf::from(&self.config.notes_dir).join(&file_name);
 
-        let mut output_file = File::create(&output_file_path).with_context(|| {
-            format!("Could not create file or directory {:?}", output_file_path)
-        })?;
+        if let Some(existing) = existing_file(&output_file_path) {
+            return Err(anyhow!(
+                "Note {} already exists: {:?}",
+                file_name.with_extension("").to_string_lossy(),
+                existing
+            ));
+        }
 
         let data = serde_json::to_value(&self).context("Could not serialize note")?;
-        get_templates(&self.config)?
-            .render_to_write(&self.template, &data, &mut output_file)
+        let contents = get_templates(&self.config)?
+            .render(&self.template, &data)
             .with_context(|| {
                 format!(
-                    "Could not write template {} to file {:?}",
+                    "Could not render template {} for {:?}",
                     self.template, output_file_path
                 )
             })?;
 
+        fs::write(&output_file_path, contents).with_context(|| {
+            format!("Could not create file or directory {:?}", output_file_path)
+        })?;
+
         Ok(output_file_path)
     }
 
@@ -105,6 +136,19 @@ impl NewNote {
                 .value_name("TEMPLATE_STRING")
                 .help("Handlebars template string for name. Ex. --name_template {{date now}}_{{name}}"),
         )
+        .arg(
+            Arg::new("date")
+                .short('d')
+                .long("date")
+                .value_name("DATE")
+                .help("Date used as `now` in templates. Ex. --date tomorrow, --date \"next tuesday 14:00\", --date 2024-03-01, --date -2d"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Fail when a template uses a variable that does not exist. Same as strict_templates = true in config.toml"),
+        )
         .arg(
             Arg::new("meta-data")
                 .short('m')
@@ -122,3 +166,20 @@ impl NewNote {
         )
     }
 }
+
+/// `path`, or a file in the same directory whose name only differs in case from it.
+fn existing_file(path: &Path) -> Option<PathBuf> {
+    if path.exists() {
+        return Some(path.to_path_buf());
+    }
+
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    fs::read_dir(path.parent()?)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .find(|p| {
+            p.file_name()
+                .is_some_and(|n| n.to_string_lossy().to_lowercase() == name)
+        })
+}
@@ -0,0 +1,92 @@
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+use crate::config::Config;
+use crate::graph::Graph;
+use crate::notes::{Note, Notes};
+
+#[derive(Debug)]
+pub struct ListOrphans {
+    config: Config,
+    exclude: Vec<String>,
+}
+
+impl ListOrphans {
+    #[tracing::instrument]
+    pub fn try_new(config: &Config, sub_matches: &ArgMatches) -> anyhow::Result<ListOrphans> {
+        let exclude = config
+            .orphans_exclude
+            .iter()
+            .chain(
+                sub_matches
+                    .get_many::<String>("exclude")
+                    .unwrap_or_default(),
+            )
+            .map(|folder| folder.trim_matches('/').to_string())
+            .collect();
+
+        Ok(ListOrphans {
+            config: config.clone(),
+            exclude,
+        })
+    }
+
+    fn is_excluded(&self, note: &Note) -> bool {
+        self.exclude
+            .iter()
+            .any(|folder| note.name.starts_with(&format!("{}/", folder)))
+    }
+
+    #[tracing::instrument]
+    pub fn run(&self) -> anyhow::Result<()> {
+        let notes = Notes::load(&self.config)?;
+        let graph = Graph::new(&notes, false);
+
+        let included: Vec<(usize, &Note)> = notes
+            .iter()
+            .enumerate()
+            .filter(|(_, note)| !self.is_excluded(note))
+            .collect();
+
+        println!("No inbound links:");
+        for (i, note) in &included {
+            if graph.inbound(*i) == 0 {
+                println!("  {}", note.name);
+            }
+        }
+
+        println!("No outbound links:");
+        for (i, note) in &included {
+            if graph.outbound(*i) == 0 {
+                println!("  {}", note.name);
+            }
+        }
+
+        println!("Isolated clusters:");
+        for component in graph.components().iter().skip(1) {
+            let names: Vec<_> = component
+                .iter()
+                .filter_map(|i| included.iter().find(|(n, _)| n == i))
+                .map(|(_, note)| note.name.as_str())
+                .collect();
+            if component.len() > 1 && !names.is_empty() {
+                println!("  {}", names.join(", "));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    pub fn cmd() -> Command {
+        Command::new("orphans")
+            .about("List notes without inbound or outbound links and isolated clusters")
+            .arg(
+                Arg::new("exclude")
+                    .short('e')
+                    .long("exclude")
+                    .value_name("FOLDER")
+                    .action(ArgAction::Append)
+                    .help("Folder in notes dir to leave out of the report. Ex. --exclude daily"),
+            )
+    }
+}
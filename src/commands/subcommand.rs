@@ -45,7 +45,7 @@ impl SubCommand {
         let data = serde_json::to_value(self)?;
         let cmd = templating::get_templates(&self.config)?.render_template(&self.cmd, &data)?;
 
-        std::process::Command::new("sh").arg("-c").arg(cmd).exec();
+        let _ = std::process::Command::new("sh").arg("-c").arg(cmd).exec();
 
         Ok(())
     }
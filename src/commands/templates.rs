@@ -0,0 +1,235 @@
+use anyhow::{anyhow, Context};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use handlebars::Template;
+use std::{fs, path::Path};
+
+use crate::config::Config;
+use crate::templating::{
+    apply_layout, embedded_template_files,
+    inspect::{description, referenced_variables},
+    template_files, Layer, TemplateFile, TemplateKind, EMPTY_TEMPLATE_NAME, LAYOUTS_DIR,
+    TEMPLATE_EXTENSION,
+};
+
+#[derive(Debug)]
+enum Action {
+    List,
+    Show(String),
+    Check,
+    Export { names: Vec<String>, force: bool },
+}
+
+#[derive(Debug)]
+pub struct Templates {
+    config: Config,
+    action: Action,
+}
+
+impl Templates {
+    #[tracing::instrument]
+    pub fn try_new(config: &Config, sub_matches: &ArgMatches) -> anyhow::Result<Templates> {
+        let action = match sub_matches.subcommand() {
+            Some(("show", show_matches)) => Action::Show(
+                show_matches
+                    .get_one::<String>("name")
+                    .cloned()
+                    .ok_or(anyhow!("Missing template name"))?,
+            ),
+            Some(("check", _)) => Action::Check,
+            Some(("export", export_matches)) => Action::Export {
+                names: export_matches
+                    .get_many::<String>("name")
+                    .map(|names| names.cloned().collect())
+                    .unwrap_or_default(),
+                force: export_matches.get_flag("force"),
+            },
+            _ => Action::List,
+        };
+
+        Ok(Templates {
+            config: config.clone(),
+            action,
+        })
+    }
+
+    #[tracing::instrument]
+    pub fn run(&self) -> anyhow::Result<()> {
+        let files = template_files(&self.config)?;
+        match &self.action {
+            Action::List => list(&files),
+            Action::Show(name) => show(&files, name),
+            Action::Check => check(&files),
+            Action::Export { names, force } => {
+                export_starters(&self.config.templates_dir, names, *force)
+            }
+        }
+    }
+
+    #[tracing::instrument]
+    pub fn cmd() -> Command {
+        Command::new("templates")
+            .about("List, show and check templates")
+            .subcommand(
+                Command::new("list")
+                    .about("List templates with the layer they come from and their description"),
+            )
+            .subcommand(
+                Command::new("show").about("Print a template").arg(
+                    Arg::new("name")
+                        .required(true)
+                        .help("Name of the template. Ex. work/meeting"),
+                ),
+            )
+            .subcommand(
+                Command::new("check")
+                    .about("Compile every template and list the variables they use"),
+            )
+            .subcommand(
+                Command::new("export")
+                    .about("Copy built-in starter templates into templates_dir to customize them")
+                    .arg(
+                        Arg::new("name")
+                            .action(ArgAction::Append)
+                            .help("Templates to export, all if none are given. Ex. daily meeting"),
+                    )
+                    .arg(
+                        Arg::new("force")
+                            .long("force")
+                            .action(ArgAction::SetTrue)
+                            .help("Overwrite templates that already exist"),
+                    ),
+            )
+    }
+}
+
+/// Copies starter templates into `templates_dir`, all of them unless `names` are given.
+pub fn export_starters(templates_dir: &Path, names: &[String], force: bool) -> anyhow::Result<()> {
+    let starters: Vec<TemplateFile> = embedded_template_files()
+        .into_iter()
+        .filter(|f| f.name != EMPTY_TEMPLATE_NAME)
+        .collect();
+
+    for name in names {
+        if !starters.iter().any(|f| &f.name == name) {
+            return Err(anyhow!(
+                "There is no built-in template {}. Available: {}",
+                name,
+                starters
+                    .iter()
+                    .map(|f| f.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+    }
+
+    for file in starters
+        .iter()
+        .filter(|f| names.is_empty() || names.contains(&f.name))
+    {
+        let path = templates_dir.join(format!("{}.{}", file.name, TEMPLATE_EXTENSION));
+        if path.exists() && !force {
+            println!(
+                "Skipped {:?}, it already exists. Use --force to overwrite",
+                path
+            );
+            continue;
+        }
+
+        fs::create_dir_all(templates_dir)
+            .with_context(|| format!("Could not create templates directory {:?}", templates_dir))?;
+        fs::write(&path, file.read()?)
+            .with_context(|| format!("Could not write template {:?}", path))?;
+        println!("{}", path.display());
+    }
+
+    Ok(())
+}
+
+fn show(files: &[TemplateFile], name: &str) -> anyhow::Result<()> {
+    let file = files.iter().find(|f| f.name == name).ok_or(anyhow!(
+        "Could not find template {} in any templates directory",
+        name
+    ))?;
+    print!("{}", file.read()?);
+    Ok(())
+}
+
+fn list(files: &[TemplateFile]) -> anyhow::Result<()> {
+    let width = files.iter().map(|f| f.name.len()).max().unwrap_or_default();
+    for file in files {
+        let description = description(&file.read()?).unwrap_or_default();
+        println!(
+            "{}",
+            format!(
+                "{:width$}  {:8}  {:8}  {}",
+                file.name,
+                file.kind.as_str(),
+                file.layer.as_str(),
+                description
+            )
+            .trim_end()
+        );
+    }
+    Ok(())
+}
+
+/// Checks the templates on disk. Built-in templates are known to compile.
+fn check(files: &[TemplateFile]) -> anyhow::Result<()> {
+    let on_disk: Vec<&TemplateFile> = files
+        .iter()
+        .filter(|f| f.layer != Layer::Embedded)
+        .collect();
+
+    let mut failed = 0;
+    for file in &on_disk {
+        let source = file.read()?;
+        let missing_layout = apply_layout(&source)
+            .map(|(layout, _)| format!("{}{}", LAYOUTS_DIR, layout))
+            .filter(|layout| {
+                file.kind == TemplateKind::Template
+                    && !files
+                        .iter()
+                        .any(|f| f.kind == TemplateKind::Layout && &f.name == layout)
+            });
+
+        match (Template::compile(&source), missing_layout) {
+            (Ok(template), None) => {
+                println!("{}: ok", file.name);
+                let variables = referenced_variables(&template);
+                if !variables.is_empty() {
+                    println!(
+                        "  variables: {}",
+                        variables.into_iter().collect::<Vec<_>>().join(", ")
+                    );
+                }
+            }
+            (Ok(_), Some(layout)) => {
+                failed += 1;
+                println!("{}: error: layout {} does not exist", file.name, layout);
+            }
+            (Err(e), _) => {
+                failed += 1;
+                match (e.line_no, e.column_no) {
+                    (Some(line), Some(column)) => println!(
+                        "{}: error at line {}, column {}: {}",
+                        file.name,
+                        line,
+                        column,
+                        e.reason()
+                    ),
+                    _ => println!("{}: error: {}", file.name, e.reason()),
+                }
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(anyhow!(
+            "{} of {} templates have errors",
+            failed,
+            on_disk.len()
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use clap::{ArgMatches, Command};
+
+use crate::config::Config;
+
+#[derive(Debug)]
+pub struct Vaults {
+    config: Config,
+}
+
+impl Vaults {
+    #[tracing::instrument]
+    pub fn try_new(config: &Config, _sub_matches: &ArgMatches) -> anyhow::Result<Vaults> {
+        Ok(Vaults {
+            config: config.clone(),
+        })
+    }
+
+    /// Lists the vaults in `config.toml` with their `notes_dir`, which is the `notes_dir` outside
+    /// of the vaults for those that do not set one. The selected vault is marked with `*`.
+    #[tracing::instrument]
+    pub fn run(&self) -> anyhow::Result<()> {
+        let width = self
+            .config
+            .vaults
+            .keys()
+            .map(|name| name.len())
+            .max()
+            .unwrap_or_default();
+        for (name, vault) in &self.config.vaults {
+            let marker = match self.config.vault.as_ref() == Some(name) {
+                true => "*",
+                false => " ",
+            };
+            let notes_dir = vault
+                .get("notes_dir")
+                .and_then(|dir| dir.as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| self.config.base_notes_dir.display().to_string());
+            println!("{} {:width$}  {}", marker, name, notes_dir);
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    pub fn cmd() -> Command {
+        Command::new("vaults")
+            .about("List the vaults defined in config.toml")
+            .subcommand(Command::new("list").about(
+                "List vaults and their notes directory. The selected vault is marked with *",
+            ))
+    }
+}
@@ -1,32 +1,191 @@
-use anyhow::Context;
+use anyhow::{anyhow, Context};
 use clap::ArgMatches;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs::{self},
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
+    sync::OnceLock,
+    time::Duration,
 };
+use time::{macros::format_description, UtcOffset};
+use toml::Spanned;
 
 use crate::utils::{parse_metadata, parse_metadata_json};
 
+static DEFAULT_EXEC_TIMEOUT_MS: u64 = 5000;
+pub static VAULT_CONFIG_FILE: &str = ".notes-cli.toml";
+static ENV_PREFIX: &str = "NOTES_CLI_";
+static REQUIRED_FIELDS: [&str; 2] = ["notes_dir", "templates_dir"];
+/// Settings that let config run commands. A `.notes-cli.toml` can come with a cloned repo or a
+/// shared folder, so only `config.toml`, env variables and flags may set them.
+static TRUSTED_FIELDS: [&str; 4] = [
+    "allow_exec",
+    "exec_allow_list",
+    "exec_timeout_ms",
+    "subcommands",
+];
+/// Settings read from env variables as they are, even when they look like a number or a bool.
+static STRING_FIELDS: [&str; 7] = [
+    "notes_dir",
+    "templates_dir",
+    "team_templates_dir",
+    "note_file_type",
+    "editor",
+    "timezone",
+    "default_vault",
+];
+static PATH_FIELDS: [&str; 3] = ["notes_dir", "templates_dir", "team_templates_dir"];
+
 fn default_file_md() -> String {
     "md".to_string()
 }
+
+/// Settings missing from every config file default to `Config::default()`, except
+/// `REQUIRED_FIELDS`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
-    #[serde(default = "default_file_md")]
     pub note_file_type: String,
     pub editor: Option<String>,
     pub notes_dir: PathBuf,
     pub templates_dir: PathBuf,
+    /// Templates shared with a team, used when neither `.templates` in `notes_dir` nor
+    /// `templates_dir` has a template with the same name
+    pub team_templates_dir: Option<PathBuf>,
 
     pub subcommands: HashMap<String, String>,
     pub meta: HashMap<String, String>,
+
+    pub orphans_exclude: Vec<String>,
+
+    /// `local`, `UTC` or an offset like `+01:00`. Defaults to `local`
+    pub timezone: Option<String>,
+
+    /// Fails rendering when a template uses a variable that does not exist. Defaults to `false`
+    pub strict_templates: bool,
+
+    /// Lets templates run commands with `{{exec}}`. Defaults to `false`
+    pub allow_exec: bool,
+    /// Programs `{{exec}}` is allowed to run. Ex. `["git", "date"]`
+    pub exec_allow_list: Vec<String>,
+    /// Milliseconds a command run by `{{exec}}` may take. Defaults to 5000
+    pub exec_timeout_ms: Option<u64>,
+
+    /// Named sets of settings, ex. `[vaults.work]`, that override the ones above when the vault
+    /// is selected with `--vault` or `default_vault`
+    pub vaults: BTreeMap<String, toml::Table>,
+    pub default_vault: Option<String>,
+    /// The selected vault
+    pub vault: Option<String>,
+    /// `notes_dir` without the settings of the selected vault
+    #[serde(skip)]
+    pub base_notes_dir: PathBuf,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            note_file_type: default_file_md(),
+            editor: None,
+            notes_dir: PathBuf::new(),
+            templates_dir: PathBuf::new(),
+            team_templates_dir: None,
+            subcommands: HashMap::new(),
+            meta: HashMap::new(),
+            orphans_exclude: vec![],
+            timezone: None,
+            strict_templates: false,
+            allow_exec: false,
+            exec_allow_list: vec![],
+            exec_timeout_ms: None,
+            vaults: BTreeMap::new(),
+            default_vault: None,
+            vault: None,
+            base_notes_dir: PathBuf::new(),
+        }
+    }
+}
+
+/// The `[vaults.*]` tables of a config file, read as `T` to check their settings or keys.
+#[derive(Deserialize)]
+struct Vaults<T> {
+    #[serde(default = "BTreeMap::new")]
+    vaults: BTreeMap<String, T>,
 }
 
 impl Config {
+    /// Merges, from lowest to highest priority, `config.toml` in the config directory, the
+    /// nearest `.notes-cli.toml` in the current directory or its parents and `NOTES_CLI_*`
+    /// environment variables. Tables like `[meta]` are merged key by key. The settings of the
+    /// selected vault apply on top of the files, below the environment variables.
     pub fn try_new(matches: &ArgMatches) -> anyhow::Result<Config> {
-        let config_path = get_config_path(matches.get_one::<String>("config-path"))?;
+        Ok(LoadedConfig::try_new(matches)?.config)
+    }
+
+    /// Fails when a directory setting points to a file, or to a directory that does not exist
+    /// and can not be created.
+    fn check_dirs(&self) -> anyhow::Result<()> {
+        let dirs = [
+            ("notes_dir", Some(&self.notes_dir)),
+            ("templates_dir", Some(&self.templates_dir)),
+            ("team_templates_dir", self.team_templates_dir.as_ref()),
+        ];
+        for (key, dir) in dirs {
+            let Some(dir) = dir else { continue };
+            if dir.exists() {
+                if !dir.is_dir() {
+                    return Err(anyhow!("{} {:?} is not a directory", key, dir));
+                }
+                continue;
+            }
+
+            let creatable = dir
+                .ancestors()
+                .find(|a| a.exists())
+                .and_then(|a| fs::metadata(a).ok())
+                .is_some_and(|m| m.is_dir() && !m.permissions().readonly());
+            if !creatable {
+                return Err(anyhow!(
+                    "{} {:?} does not exist and can not be created",
+                    key,
+                    dir
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn clone_add_meta(&self, meta: HashMap<String, String>) -> Config {
+        let mut c = self.clone();
+        c.meta = meta.into_iter().chain(self.meta.clone()).collect();
+        c.clone()
+    }
+
+    pub fn exec_timeout(&self) -> Duration {
+        Duration::from_millis(self.exec_timeout_ms.unwrap_or(DEFAULT_EXEC_TIMEOUT_MS))
+    }
+
+    pub fn utc_offset(&self) -> anyhow::Result<UtcOffset> {
+        parse_timezone(self.timezone.as_deref().unwrap_or("local"))
+    }
+}
+
+/// Where each setting comes from, by its dotted key, ex. `meta.author`. A config file's path,
+/// `vault <name>`, `env` or `flag`.
+pub type Sources = BTreeMap<String, String>;
+
+/// A `Config` with the source of each setting.
+#[derive(Debug)]
+pub struct LoadedConfig {
+    pub config: Config,
+    pub sources: Sources,
+}
+
+impl LoadedConfig {
+    /// See `Config::try_new`.
+    pub fn try_new(matches: &ArgMatches) -> anyhow::Result<LoadedConfig> {
         let mut additional_metadata = parse_metadata(matches.get_many::<String>("meta-data"));
         let additional_metadata_json =
             parse_metadata_json(matches.get_many::<String>("meta-data-json"));
@@ -34,32 +193,455 @@ impl Config {
             additional_metadata.insert(key, value);
         }
 
-        let filename = config_path.join("config.toml");
+        let files = config_files(matches)?;
+        let mut sources = Sources::new();
+        let mut merged = toml::Table::new();
+        for file in &files {
+            let mut table = read_config_file(file)?;
+            if file.file_name() == Some(VAULT_CONFIG_FILE.as_ref()) {
+                for key in remove_trusted_settings(&mut table) {
+                    eprintln!(
+                        "Warning: {:?}: {} is ignored, it can only be set in config.toml",
+                        file, key
+                    );
+                }
+            }
+            record_sources(&mut sources, "", &table, &file.display().to_string());
+            merge_tables(&mut merged, table);
+        }
+        let cwd = std::env::current_dir().context("Could not read current directory")?;
+        let mut env = env_overrides(std::env::vars());
+        resolve_paths(&mut env, &cwd).context("Could not read NOTES_CLI_ environment variables")?;
+        let vault = matches.get_one::<String>("vault").cloned().or_else(|| {
+            env.get("default_vault")
+                .or(merged.get("default_vault"))
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+        });
+        let base_notes_dir = merged
+            .get("notes_dir")
+            .and_then(|dir| dir.as_str())
+            .map(PathBuf::from);
+        if let Some(vault) = vault {
+            let settings = select_vault(&mut merged, &vault)?;
+            record_sources(&mut sources, "", &settings, &format!("vault {}", vault));
+        }
+        record_sources(&mut sources, "", &env, "env");
+        merge_tables(&mut merged, env);
 
-        let contents = fs::read_to_string(&filename)
-            .with_context(|| format!("Could not find config file: {:?}", filename))?;
+        for key in REQUIRED_FIELDS {
+            if !merged.contains_key(key) {
+                return Err(anyhow!(
+                    "{} is not set. Add it to {:?} or set {}{}",
+                    key,
+                    files.last().unwrap_or(&user_config_file(matches)?),
+                    ENV_PREFIX,
+                    key.to_uppercase()
+                ));
+            }
+        }
+
+        let mut config = merged
+            .try_into::<Config>()
+            .context("Invalid value in NOTES_CLI_ environment variables")?;
+        config.base_notes_dir = base_notes_dir.unwrap_or(config.notes_dir.clone());
+        config.check_dirs()?;
 
-        toml::from_str::<Config>(&contents)
-            .with_context(|| format!("Could not construct Config from {:?}", contents))
-            .map(|c| c.clone_add_meta(additional_metadata))
+        // Metadata in config files takes precedence over metadata passed as flags
+        for key in additional_metadata.keys() {
+            sources
+                .entry(format!("meta.{}", key))
+                .or_insert("flag".to_string());
+        }
+        Ok(LoadedConfig {
+            config: config.clone_add_meta(additional_metadata),
+            sources,
+        })
     }
+}
 
-    pub fn clone_add_meta(&self, meta: HashMap<String, String>) -> Config {
-        let mut c = self.clone();
-        c.meta = meta.into_iter().chain(self.meta.clone()).collect();
-        c.clone()
+/// Records `source` for every value in `table`, replacing earlier sources.
+fn record_sources(sources: &mut Sources, prefix: &str, table: &toml::Table, source: &str) {
+    for (key, value) in table {
+        let key = format!("{}{}", prefix, key);
+        match value {
+            toml::Value::Table(table) => {
+                record_sources(sources, &format!("{}.", key), table, source)
+            }
+            _ => {
+                sources.insert(key, source.to_string());
+            }
+        }
     }
 }
 
+/// `config.toml` in the config directory.
+pub fn user_config_file(matches: &ArgMatches) -> anyhow::Result<PathBuf> {
+    Ok(get_config_path(matches.get_one::<String>("config-path"))?.join("config.toml"))
+}
+
+/// The config files that are read, from lowest to highest priority.
+pub fn config_files(matches: &ArgMatches) -> anyhow::Result<Vec<PathBuf>> {
+    let filename = user_config_file(matches)?;
+    let mut files = vec![];
+    // The config directory is optional when it is not passed explicitly and a vault config exists
+    if filename.exists() || matches.get_one::<String>("config-path").is_some() {
+        files.push(filename.clone());
+    }
+    let cwd = std::env::current_dir().context("Could not read current directory")?;
+    files.extend(find_vault_config(&cwd));
+    if files.is_empty() {
+        return Err(anyhow!("Could not find config file: {:?}", filename));
+    }
+    Ok(files)
+}
+
+pub fn parse_timezone(timezone: &str) -> anyhow::Result<UtcOffset> {
+    match timezone.trim() {
+        "local" => UtcOffset::current_local_offset().map_err(|e| {
+            anyhow!(
+                "Could not determine local timezone: {}. Set timezone to UTC or an offset like +01:00",
+                e
+            )
+        }),
+        "UTC" | "utc" | "Z" => Ok(UtcOffset::UTC),
+        offset => UtcOffset::parse(
+            offset,
+            format_description!("[offset_hour sign:mandatory]:[offset_minute]"),
+        )
+        .or_else(|_| UtcOffset::parse(offset, format_description!("[offset_hour sign:mandatory]")))
+        .map_err(|_| {
+            anyhow!(
+                "Invalid timezone {:?}, use local, UTC or an offset like +01:00",
+                offset
+            )
+        }),
+    }
+}
+
+/// The config directory: `--config-path`, `$XDG_CONFIG_HOME/notes-cli` or `~/.config/notes-cli`.
 pub fn get_config_path(flag: Option<&String>) -> anyhow::Result<PathBuf> {
-    let path_flag = flag.map(|c| Path::new(c).to_path_buf()).ok_or(());
+    if let Some(path) = flag {
+        return Ok(PathBuf::from(path));
+    }
 
-    match path_flag {
-        Ok(path) => Ok(path),
-        Err(_) => {
-            let xdg_config_home = std::env::var("XDG_CONFIG_HOME")
-                .context("Could not find env variable XDG_CONFIG_HOME")?;
-            Ok(Path::new(&xdg_config_home).join("notes-cli"))
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .map(|dir| dir.join("notes-cli"))
+        .ok_or(anyhow!(
+            "Could not find config directory, neither XDG_CONFIG_HOME nor HOME is set. Pass --config-path"
+        ))
+}
+
+/// The nearest `.notes-cli.toml` in `dir` or one of its parents.
+pub fn find_vault_config(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|d| d.join(VAULT_CONFIG_FILE))
+        .find(|f| f.is_file())
+}
+
+/// Config values from `NOTES_CLI_*` variables. Ex. `NOTES_CLI_NOTES_DIR=~/notes` sets `notes_dir`
+/// and `NOTES_CLI_META__AUTHOR=Max` sets `author` in `[meta]`. Values of other than string
+/// settings are read as TOML when they can be, ex. `true` or `["git"]`.
+pub fn env_overrides(vars: impl Iterator<Item = (String, String)>) -> toml::Table {
+    let mut overrides = toml::Table::new();
+    for (name, value) in vars {
+        let Some(key) = name.strip_prefix(ENV_PREFIX) else {
+            continue;
+        };
+        let path: Vec<String> = key.split("__").map(|k| k.to_lowercase()).collect();
+        let value = match is_string_setting(&path) {
+            true => toml::Value::String(value),
+            false => parse_value(&value),
+        };
+
+        let Some((last, tables)) = path.split_last() else {
+            continue;
+        };
+        let mut table = toml::Table::new();
+        table.insert(last.clone(), value);
+        for key in tables.iter().rev() {
+            let mut parent = toml::Table::new();
+            parent.insert(key.clone(), toml::Value::Table(table));
+            table = parent;
+        }
+        merge_tables(&mut overrides, table);
+    }
+    overrides
+}
+
+/// Whether the setting at `path` is a string, ex. `notes_dir`, `meta.ticket` or
+/// `vaults.work.notes_dir`.
+pub fn is_string_setting(path: &[String]) -> bool {
+    let path = match path {
+        [vaults, _, rest @ ..] if vaults == "vaults" => rest,
+        path => path,
+    };
+    match path {
+        [table, _, ..] => table == "meta" || table == "subcommands",
+        [key] => STRING_FIELDS.contains(&key.as_str()),
+        [] => false,
+    }
+}
+
+/// Whether a dotted key is one of `TRUSTED_FIELDS`, ex. `subcommands.find` or
+/// `vaults.work.allow_exec`.
+pub fn is_trusted_setting(key: &str) -> bool {
+    let parts: Vec<&str> = key.split('.').collect();
+    match parts.as_slice() {
+        ["vaults", _, setting, ..] | [setting, ..] => TRUSTED_FIELDS.contains(setting),
+        [] => false,
+    }
+}
+
+/// Removes `TRUSTED_FIELDS` from `table` and its vaults, and returns their dotted keys.
+fn remove_trusted_settings(table: &mut toml::Table) -> Vec<String> {
+    let mut removed = vec![];
+    for key in TRUSTED_FIELDS {
+        if table.remove(key).is_some() {
+            removed.push(key.to_string());
+        }
+    }
+    if let Some(toml::Value::Table(vaults)) = table.get_mut("vaults") {
+        for (name, vault) in vaults.iter_mut() {
+            if let toml::Value::Table(vault) = vault {
+                removed.extend(
+                    remove_trusted_settings(vault)
+                        .into_iter()
+                        .map(|key| format!("vaults.{}.{}", name, key)),
+                );
+            }
+        }
+    }
+    removed
+}
+
+/// Reads a config file, checking every setting and warning about keys that are not settings.
+/// Paths are resolved relative to the file.
+fn read_config_file(file: &Path) -> anyhow::Result<toml::Table> {
+    let contents = fs::read_to_string(file)
+        .with_context(|| format!("Could not find config file: {:?}", file))?;
+    let mut table = contents
+        .parse::<toml::Table>()
+        .with_context(|| format!("Could not parse config file {:?}", file))?;
+    check_settings(&contents, file)?;
+
+    for (key, line) in unknown_keys(&contents)? {
+        eprintln!(
+            "Warning: {:?} line {}: unknown setting {} is ignored",
+            file, line, key
+        );
+    }
+
+    let dir = std::path::absolute(file)?
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    resolve_paths(&mut table, &dir)
+        .with_context(|| format!("Could not read config file {:?}", file))?;
+    Ok(table)
+}
+
+/// Fails with the setting and its line when `contents` of `file` has an invalid setting.
+pub fn check_settings(contents: &str, file: &Path) -> anyhow::Result<()> {
+    if let Err(e) = toml::from_str::<Config>(contents)
+        .map(|_| ())
+        .and_then(|_| toml::from_str::<Vaults<Config>>(contents).map(|_| ()))
+    {
+        // The setting is the key on the line of the invalid value, in the last table before it
+        let setting = e.span().and_then(|span| {
+            let line = contents[..span.start].matches('\n').count() + 1;
+            let (key, _) = contents.lines().nth(line - 1)?.split_once('=')?;
+            let key = match contents
+                .lines()
+                .take(line - 1)
+                .filter_map(|l| {
+                    l.split('#')
+                        .next()?
+                        .trim()
+                        .strip_prefix('[')?
+                        .strip_suffix(']')
+                })
+                .last()
+            {
+                Some(table) => format!("{}.{}", table.trim(), key.trim()),
+                None => key.trim().to_string(),
+            };
+            Some(format!(" {} on line {}", key, line))
+        });
+        return Err(anyhow!(e).context(format!(
+            "Invalid setting{} in config file {:?}",
+            setting.unwrap_or_default(),
+            file
+        )));
+    }
+    Ok(())
+}
+
+/// Keys in `contents` that are not settings, with their line, ex. `("vaults.work.notes", 12)`.
+pub fn unknown_keys(contents: &str) -> anyhow::Result<Vec<(String, usize)>> {
+    let line = |key: &Spanned<String>| contents[..key.span().start].matches('\n').count() + 1;
+
+    let mut unknown = vec![];
+    let keys: BTreeMap<Spanned<String>, toml::Value> = toml::from_str(contents)?;
+    for key in keys.keys() {
+        if !is_setting(key.get_ref(), false) {
+            unknown.push((key.get_ref().clone(), line(key)));
+        }
+    }
+    for (vault, keys) in
+        toml::from_str::<Vaults<BTreeMap<Spanned<String>, toml::Value>>>(contents)?.vaults
+    {
+        for key in keys.keys() {
+            if !is_setting(key.get_ref(), true) {
+                unknown.push((format!("vaults.{}.{}", vault, key.get_ref()), line(key)));
+            }
+        }
+    }
+    unknown.sort_by_key(|(_, line)| *line);
+    Ok(unknown)
+}
+
+/// Whether `key` is a setting of `Config`, or of a vault when `in_vault`.
+fn is_setting(key: &str, in_vault: bool) -> bool {
+    static SETTINGS: OnceLock<Vec<String>> = OnceLock::new();
+    let settings = SETTINGS.get_or_init(|| match serde_json::to_value(Config::default()) {
+        Ok(serde_json::Value::Object(settings)) => settings.keys().cloned().collect(),
+        _ => unreachable!("Config serializes to an object"),
+    });
+
+    settings.iter().any(|s| s == key)
+        && !(in_vault && ["vaults", "default_vault", "vault"].contains(&key))
+}
+
+/// Whether a dotted key can be set in a config file, ex. `notes_dir`, `meta.author` or
+/// `vaults.work.notes_dir`.
+pub fn is_setting_key(key: &str) -> bool {
+    let parts: Vec<&str> = key.split('.').collect();
+    let (parts, in_vault) = match parts.as_slice() {
+        ["vaults", _, rest @ ..] => (rest, true),
+        parts => (parts, false),
+    };
+    match parts {
+        [table, _] => ["meta", "subcommands"].contains(table),
+        // `vault` is picked with --vault or NOTES_CLI_VAULT when running
+        [key] => is_setting(key, in_vault) && !["vaults", "vault"].contains(key),
+        _ => false,
+    }
+}
+
+/// Expands `~` and `$VAR` or `${VAR}` in `path` and makes it absolute, relative to `base`.
+pub fn expand_path(path: &str, base: &Path) -> anyhow::Result<PathBuf> {
+    static VAR: OnceLock<Regex> = OnceLock::new();
+    let var = VAR.get_or_init(|| {
+        Regex::new(r"\$(?:\{([A-Za-z_][A-Za-z0-9_]*)\}|([A-Za-z_][A-Za-z0-9_]*))")
+            .expect("valid regex")
+    });
+
+    let mut missing = None;
+    let expanded = var.replace_all(path, |c: &regex::Captures| {
+        let name = c.get(1).or(c.get(2)).map_or("", |m| m.as_str());
+        std::env::var(name).unwrap_or_else(|_| {
+            missing.get_or_insert(name.to_string());
+            String::new()
+        })
+    });
+    if let Some(name) = missing {
+        return Err(anyhow!("Could not expand {}, ${} is not set", path, name));
+    }
+
+    let expanded = match expanded.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            let home = std::env::var("HOME")
+                .map_err(|_| anyhow!("Could not expand {}, $HOME is not set", path))?;
+            format!("{}{}", home, rest)
+        }
+        _ => expanded.to_string(),
+    };
+
+    Ok(normalize(&base.join(expanded)))
+}
+
+/// Removes `.` and `..` from `path` without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Expands the paths in `config` and in each of its vaults, relative to `base`.
+fn resolve_paths(config: &mut toml::Table, base: &Path) -> anyhow::Result<()> {
+    let mut tables = vec![config];
+    while let Some(table) = tables.pop() {
+        for (key, value) in table.iter_mut() {
+            match value {
+                toml::Value::String(path) if PATH_FIELDS.contains(&key.as_str()) => {
+                    let expanded = expand_path(path, base)
+                        .with_context(|| format!("Invalid path in {}", key))?;
+                    *path = expanded.to_string_lossy().to_string();
+                }
+                toml::Value::Table(vaults) if key == "vaults" => {
+                    tables.extend(vaults.iter_mut().filter_map(|(_, v)| v.as_table_mut()));
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Applies the settings in `[vaults.<name>]` to `config`, and returns them.
+fn select_vault(config: &mut toml::Table, name: &str) -> anyhow::Result<toml::Table> {
+    let vaults = config.get("vaults").and_then(|v| v.as_table());
+    let vault = vaults
+        .and_then(|vaults| vaults.get(name))
+        .and_then(|v| v.as_table())
+        .cloned()
+        .ok_or_else(|| {
+            anyhow!(
+                "Could not find vault {}. Available: {}",
+                name,
+                vaults
+                    .map(|vaults| vaults.keys().cloned().collect::<Vec<_>>().join(", "))
+                    .unwrap_or_default()
+            )
+        })?;
+
+    let mut settings = vault;
+    settings.insert("vault".to_string(), toml::Value::String(name.to_string()));
+    merge_tables(config, settings.clone());
+    Ok(settings)
+}
+
+/// `value` as a TOML value, ex. `true` or `["git"]`, or else as a string.
+pub fn parse_value(value: &str) -> toml::Value {
+    format!("value = {}", value)
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|mut t| t.remove("value"))
+        .unwrap_or(toml::Value::String(value.to_string()))
+}
+
+/// Merges `other` into `base`, key by key for tables present in both.
+fn merge_tables(base: &mut toml::Table, other: toml::Table) {
+    for (key, value) in other {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(other)) => {
+                merge_tables(base, other)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
         }
     }
 }
@@ -89,6 +671,7 @@ mod test {
                     .long("config-path")
                     .help("Provide a path to the config directory. Default is $XDG_CONFIG_HOME"),
             )
+            .arg(Arg::new("vault").long("vault"))
             .arg(
                 Arg::new("meta-data")
                     .short('m')
@@ -162,4 +745,325 @@ mod test {
             }
         };
     }
+
+    #[test]
+    fn merges_tables_key_by_key() {
+        let mut base: toml::Table =
+            toml::from_str("notes_dir = \"/a\"\n[meta]\nwho = \"Max\"\nteam = \"core\"\n").unwrap();
+        merge_tables(
+            &mut base,
+            toml::from_str("notes_dir = \"/b\"\n[meta]\nwho = \"Sam\"\n").unwrap(),
+        );
+
+        assert_eq!(
+            toml::from_str::<toml::Table>(
+                "notes_dir = \"/b\"\n[meta]\nwho = \"Sam\"\nteam = \"core\"\n"
+            )
+            .unwrap(),
+            base
+        );
+    }
+
+    #[test]
+    fn reads_env_overrides() {
+        let vars = [
+            ("NOTES_CLI_NOTES_DIR", "/tmp/notes"),
+            ("NOTES_CLI_ALLOW_EXEC", "true"),
+            ("NOTES_CLI_EXEC_ALLOW_LIST", "[\"git\"]"),
+            ("NOTES_CLI_META__AUTHOR", "Max Hill"),
+            ("HOME", "/root"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+
+        assert_eq!(
+            toml::from_str::<toml::Table>(
+                "notes_dir = \"/tmp/notes\"\nallow_exec = true\nexec_allow_list = [\"git\"]\n[meta]\nauthor = \"Max Hill\"\n"
+            )
+            .unwrap(),
+            env_overrides(vars.into_iter())
+        );
+    }
+
+    #[test]
+    fn reads_string_settings_from_env_as_strings() {
+        let vars = [
+            ("NOTES_CLI_META__TICKET", "1234"),
+            ("NOTES_CLI_NOTES_DIR", "2024"),
+            ("NOTES_CLI_SUBCOMMANDS__YES", "true"),
+            ("NOTES_CLI_VAULTS__WORK__TEMPLATES_DIR", "2025"),
+            ("NOTES_CLI_VAULTS__WORK__META__YEAR", "2025"),
+            ("NOTES_CLI_EXEC_TIMEOUT_MS", "100"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+
+        let overrides = env_overrides(vars.into_iter());
+        assert_eq!(
+            toml::from_str::<toml::Table>(
+                r#"
+                notes_dir = "2024"
+                exec_timeout_ms = 100
+                [meta]
+                ticket = "1234"
+                [subcommands]
+                yes = "true"
+                [vaults.work]
+                templates_dir = "2025"
+                [vaults.work.meta]
+                year = "2025"
+                "#
+            )
+            .unwrap(),
+            overrides
+        );
+
+        let mut config: toml::Table = toml::from_str("templates_dir = \"/t\"").unwrap();
+        merge_tables(&mut config, overrides);
+        let config: Config = config.try_into().unwrap();
+        assert_eq!(Some(&"1234".to_string()), config.meta.get("ticket"));
+        assert_eq!(PathBuf::from("2024"), config.notes_dir);
+    }
+
+    fn config_file(contents: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("notes-cli-config-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("config.toml"), contents).unwrap();
+        dir
+    }
+
+    #[test]
+    fn reports_invalid_settings_with_their_line() {
+        let dir =
+            config_file("notes_dir = \"/tmp\"\ntemplates_dir = \"/tmp\"\nallow_exec = \"yes\"\n");
+        let error = format!(
+            "{:#}",
+            read_config_file(&dir.join("config.toml")).unwrap_err()
+        );
+        assert!(error.contains("Invalid setting allow_exec on line 3 in config file"));
+        assert!(error.contains("line 3, column 14"));
+        assert!(error.contains("expected a boolean"));
+
+        fs::write(
+            dir.join("config.toml"),
+            "[vaults.work]\nexec_timeout_ms = \"soon\"\n",
+        )
+        .unwrap();
+        let error = format!(
+            "{:#}",
+            read_config_file(&dir.join("config.toml")).unwrap_err()
+        );
+        assert!(error.contains("Invalid setting vaults.work.exec_timeout_ms on line 2"));
+        assert!(error.contains("line 2, column 19"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn finds_unknown_keys() {
+        assert_eq!(
+            vec![
+                ("notes_directory".to_string(), 2),
+                ("vaults.work.colour".to_string(), 7),
+                ("vaults.work.vaults".to_string(), 9),
+            ],
+            unknown_keys(
+                "notes_dir = \"/a\"\nnotes_directory = \"/b\"\n[meta]\nanything = \"goes\"\n[vaults.work]\nnotes_dir = \"/c\"\ncolour = \"red\"\n[vaults.work.meta]\n[vaults.work.vaults]\n"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn tables_are_optional_and_directories_are_checked() {
+        let dir = config_file("notes_dir = \"./notes\"\ntemplates_dir = \"./templates\"\n");
+        let matches = |dir: &Path| get_matches_from(dir, None);
+
+        let config = Config::try_new(&matches(&dir)).unwrap();
+        assert!(config.subcommands.is_empty());
+        assert!(config.meta.is_empty());
+
+        fs::write(dir.join("notes"), "").unwrap();
+        assert_eq!(
+            format!("notes_dir {:?} is not a directory", dir.join("notes")),
+            Config::try_new(&matches(&dir)).unwrap_err().to_string()
+        );
+
+        fs::write(dir.join("config.toml"), "notes_dir = \"./notes-dir\"\n").unwrap();
+        assert_eq!(
+            format!(
+                "templates_dir is not set. Add it to {:?} or set NOTES_CLI_TEMPLATES_DIR",
+                dir.join("config.toml")
+            ),
+            Config::try_new(&matches(&dir)).unwrap_err().to_string()
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn expands_paths() {
+        let base = Path::new("/home/max/.config/notes-cli");
+        std::env::set_var("NOTES_CLI_TEST_DIR", "/srv/notes");
+        let home = std::env::var("HOME").unwrap();
+
+        assert_eq!(
+            PathBuf::from("/home/max/.config/notes-cli/templates"),
+            expand_path("./templates", base).unwrap()
+        );
+        assert_eq!(
+            PathBuf::from("/home/max/shared"),
+            expand_path("../../shared", base).unwrap()
+        );
+        assert_eq!(
+            Path::new(&home).join("notes"),
+            expand_path("~/notes", base).unwrap()
+        );
+        assert_eq!(
+            PathBuf::from("/srv/notes/work"),
+            expand_path("$NOTES_CLI_TEST_DIR/work", base).unwrap()
+        );
+        assert_eq!(
+            PathBuf::from("/srv/notes-2"),
+            expand_path("${NOTES_CLI_TEST_DIR}-2", base).unwrap()
+        );
+        assert_eq!(
+            PathBuf::from("/home/max/.config/notes-cli/~notes"),
+            expand_path("~notes", base).unwrap()
+        );
+        assert_eq!(
+            "Could not expand $NOTES_CLI_TEST_MISSING/notes, $NOTES_CLI_TEST_MISSING is not set",
+            expand_path("$NOTES_CLI_TEST_MISSING/notes", base)
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn resolves_paths_in_vaults() {
+        let mut config: toml::Table = toml::from_str(
+            r#"
+            notes_dir = "notes"
+            editor = "./vim"
+            [vaults.work]
+            templates_dir = "../work/templates"
+            "#,
+        )
+        .unwrap();
+        resolve_paths(&mut config, Path::new("/config")).unwrap();
+
+        assert_eq!(
+            toml::from_str::<toml::Table>(
+                r#"
+                notes_dir = "/config/notes"
+                editor = "./vim"
+                [vaults.work]
+                templates_dir = "/work/templates"
+                "#
+            )
+            .unwrap(),
+            config
+        );
+    }
+
+    #[test]
+    fn selects_vault() {
+        let mut config: toml::Table = toml::from_str(
+            r#"
+            notes_dir = "/notes"
+            templates_dir = "/templates"
+            [subcommands]
+            [meta]
+            who = "Max"
+            [vaults.work]
+            notes_dir = "/work/notes"
+            [vaults.work.meta]
+            team = "core"
+            [vaults.personal]
+            notes_dir = "/personal"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            "Could not find vault home. Available: personal, work",
+            select_vault(&mut config.clone(), "home")
+                .unwrap_err()
+                .to_string()
+        );
+
+        select_vault(&mut config, "work").unwrap();
+        let config: Config = config.try_into().unwrap();
+        assert_eq!(Some("work".to_string()), config.vault);
+        assert_eq!(PathBuf::from("/work/notes"), config.notes_dir);
+        assert_eq!(PathBuf::from("/templates"), config.templates_dir);
+        assert_eq!(Some(&"Max".to_string()), config.meta.get("who"));
+        assert_eq!(Some(&"core".to_string()), config.meta.get("team"));
+    }
+
+    #[test]
+    fn removes_trusted_settings() {
+        let mut table: toml::Table = toml::from_str(
+            r#"
+            notes_dir = "/notes"
+            allow_exec = true
+            exec_allow_list = ["sh"]
+            [subcommands]
+            run = "sh"
+            [vaults.work]
+            exec_timeout_ms = 1
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                "allow_exec",
+                "exec_allow_list",
+                "subcommands",
+                "vaults.work.exec_timeout_ms"
+            ],
+            remove_trusted_settings(&mut table)
+        );
+        assert_eq!(
+            toml::from_str::<toml::Table>("notes_dir = \"/notes\"\n[vaults.work]\n").unwrap(),
+            table
+        );
+    }
+
+    #[test]
+    fn finds_vault_config_in_parent_directories() {
+        let dir = std::env::temp_dir().join(format!("notes-cli-vault-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("projects/roadmap")).unwrap();
+        fs::write(dir.join(VAULT_CONFIG_FILE), "").unwrap();
+
+        assert_eq!(
+            Some(dir.join(VAULT_CONFIG_FILE)),
+            find_vault_config(&dir.join("projects/roadmap"))
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn parses_timezones() {
+        assert_eq!(UtcOffset::UTC, parse_timezone("UTC").unwrap());
+        assert_eq!(
+            UtcOffset::from_hms(1, 0, 0).unwrap(),
+            parse_timezone("+01:00").unwrap()
+        );
+        assert_eq!(
+            UtcOffset::from_hms(-5, -30, 0).unwrap(),
+            parse_timezone("-05:30").unwrap()
+        );
+        assert_eq!(
+            UtcOffset::from_hms(2, 0, 0).unwrap(),
+            parse_timezone("+02").unwrap()
+        );
+        // The local offset is unknown while other tests run on their own threads
+        if let Err(e) = parse_timezone("local") {
+            assert!(e.to_string().contains("Could not determine local timezone"));
+        }
+        assert_eq!(
+            "Invalid timezone \"Mars/Olympus\", use local, UTC or an offset like +01:00",
+            parse_timezone("Mars/Olympus").unwrap_err().to_string()
+        );
+    }
 }
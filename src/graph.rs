@@ -0,0 +1,232 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use crate::notes::Notes;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeKind {
+    Link,
+    Embed,
+    Tag,
+}
+
+impl EdgeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeKind::Link => "link",
+            EdgeKind::Embed => "embed",
+            EdgeKind::Tag => "tag",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Edge {
+    pub source: usize,
+    pub target: usize,
+    pub kind: EdgeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+/// Link graph over all notes. Nodes are indexes into `Notes`.
+#[derive(Debug)]
+pub struct Graph<'a> {
+    pub notes: &'a Notes,
+    pub nodes: BTreeSet<usize>,
+    pub edges: BTreeSet<Edge>,
+}
+
+impl<'a> Graph<'a> {
+    /// Builds link and embed edges, plus tag co-occurrence edges if `tag_edges` is set.
+    pub fn new(notes: &'a Notes, tag_edges: bool) -> Graph<'a> {
+        let mut edges = BTreeSet::new();
+
+        for (source, note) in notes.iter().enumerate() {
+            for link in &note.links {
+                match notes.resolve_link(note, link) {
+                    Some(target) if target != source => {
+                        edges.insert(Edge {
+                            source,
+                            target,
+                            kind: if link.embed {
+                                EdgeKind::Embed
+                            } else {
+                                EdgeKind::Link
+                            },
+                            tag: None,
+                        });
+                    }
+                    Some(_) => {}
+                    None => tracing::debug!("Unresolved link {:?} in {}", link.target, note.name),
+                }
+            }
+        }
+
+        if tag_edges {
+            let mut by_tag: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+            for (i, note) in notes.iter().enumerate() {
+                for tag in &note.tags {
+                    by_tag.entry(tag).or_default().push(i);
+                }
+            }
+            for (tag, members) in by_tag {
+                for (n, source) in members.iter().enumerate() {
+                    for target in &members[n + 1..] {
+                        edges.insert(Edge {
+                            source: *source,
+                            target: *target,
+                            kind: EdgeKind::Tag,
+                            tag: Some(tag.to_string()),
+                        });
+                    }
+                }
+            }
+        }
+
+        Graph {
+            notes,
+            nodes: (0..notes.len()).collect(),
+            edges,
+        }
+    }
+
+    /// Keeps only the nodes within `depth` edges of `root`, in either direction.
+    pub fn neighbourhood(self, root: usize, depth: usize) -> Graph<'a> {
+        let mut seen = BTreeSet::from([root]);
+        let mut queue = VecDeque::from([(root, 0)]);
+
+        while let Some((node, distance)) = queue.pop_front() {
+            if distance == depth {
+                continue;
+            }
+            for edge in &self.edges {
+                let next = if edge.source == node {
+                    edge.target
+                } else if edge.target == node {
+                    edge.source
+                } else {
+                    continue;
+                };
+                if seen.insert(next) {
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+
+        let edges = self
+            .edges
+            .into_iter()
+            .filter(|e| seen.contains(&e.source) && seen.contains(&e.target))
+            .collect();
+
+        Graph {
+            notes: self.notes,
+            nodes: seen,
+            edges,
+        }
+    }
+
+    pub fn inbound(&self, node: usize) -> usize {
+        self.edges.iter().filter(|e| e.target == node).count()
+    }
+
+    pub fn outbound(&self, node: usize) -> usize {
+        self.edges.iter().filter(|e| e.source == node).count()
+    }
+
+    /// Groups of nodes connected by edges in either direction, largest first.
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let mut remaining = self.nodes.clone();
+        let mut components = vec![];
+
+        while let Some(start) = remaining.pop_first() {
+            let mut component = vec![start];
+            let mut queue = VecDeque::from([start]);
+            while let Some(node) = queue.pop_front() {
+                for edge in &self.edges {
+                    let next = if edge.source == node {
+                        edge.target
+                    } else if edge.target == node {
+                        edge.source
+                    } else {
+                        continue;
+                    };
+                    if remaining.remove(&next) {
+                        component.push(next);
+                        queue.push_back(next);
+                    }
+                }
+            }
+            component.sort();
+            components.push(component);
+        }
+
+        components.sort_by_key(|c| std::cmp::Reverse(c.len()));
+        components
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::notes::Note;
+    use std::path::Path;
+
+    fn notes() -> Notes {
+        Notes::from_notes(vec![
+            Note::parse("a", Path::new("a.md"), "[[b]] #shared"),
+            Note::parse("b", Path::new("b.md"), "![[c]]"),
+            Note::parse("c", Path::new("c.md"), "[[d]]"),
+            Note::parse("d", Path::new("d.md"), "#shared"),
+        ])
+    }
+
+    #[test]
+    fn builds_link_embed_and_tag_edges() {
+        let notes = notes();
+        let graph = Graph::new(&notes, true);
+
+        let kinds: Vec<_> = graph
+            .edges
+            .iter()
+            .map(|e| (e.source, e.target, e.kind))
+            .collect();
+        assert_eq!(
+            vec![
+                (0, 1, EdgeKind::Link),
+                (0, 3, EdgeKind::Tag),
+                (1, 2, EdgeKind::Embed),
+                (2, 3, EdgeKind::Link),
+            ],
+            kinds
+        );
+    }
+
+    #[test]
+    fn limits_graph_to_neighbourhood() {
+        let notes = notes();
+        let graph = Graph::new(&notes, false).neighbourhood(1, 1);
+
+        assert_eq!(BTreeSet::from([0, 1, 2]), graph.nodes);
+        assert_eq!(2, graph.edges.len());
+    }
+
+    #[test]
+    fn finds_connected_components() {
+        let notes = Notes::from_notes(vec![
+            Note::parse("a", Path::new("a.md"), "[[b]]"),
+            Note::parse("b", Path::new("b.md"), "[[c]]"),
+            Note::parse("c", Path::new("c.md"), ""),
+            Note::parse("d", Path::new("d.md"), "[[e]]"),
+            Note::parse("e", Path::new("e.md"), ""),
+            Note::parse("f", Path::new("f.md"), ""),
+        ]);
+        let graph = Graph::new(&notes, false);
+
+        assert_eq!(vec![vec![0, 1, 2], vec![3, 4], vec![5]], graph.components());
+        assert_eq!(0, graph.inbound(0));
+        assert_eq!(1, graph.outbound(0));
+    }
+}
@@ -1,9 +1,15 @@
 use clap::{command, Arg, ArgAction, Command};
-use commands::{ls::ListNotes, new_note::NewNote, subcommand::SubCommand};
-use config::Config;
+use commands::{
+    config::ConfigCommand, graph::ExportGraph, init::Init, ls::ListNotes, new_note::NewNote,
+    orphans::ListOrphans, subcommand::SubCommand, templates::Templates, vaults::Vaults,
+};
+use config::{get_config_path, Config};
 
 mod commands;
 mod config;
+mod graph;
+mod natural_date;
+mod notes;
 mod templating;
 mod utils;
 
@@ -18,7 +24,13 @@ pub fn cmd() -> Command {
             Arg::new("config-path")
                 .short('c')
                 .long("config-path")
-                .help("Provide a path to the config directory. Default is $XDG_CONFIG_HOME/notes-cli/cofnig.toml"),
+                .help("Provide a path to the config directory. Default is $XDG_CONFIG_HOME/notes-cli or ~/.config/notes-cli. Settings in .notes-cli.toml in the current directory or its parents and NOTES_CLI_* environment variables take precedence"),
+        )
+        .arg(
+            Arg::new("vault")
+                .long("vault")
+                .global(true)
+                .help("Use the settings of a vault defined as [vaults.NAME] in config.toml. Default is default_vault"),
         )
         .arg(
             Arg::new("meta-data")
@@ -34,8 +46,14 @@ pub fn cmd() -> Command {
                 .value_name("json")
                 .action(ArgAction::Append)
                 .help("Aditional key value pairs to be added to config, passed as json. Ex. --meta-data-json \"{\"name\": \"John\"}\""),)
+        .subcommand(Init::cmd())
+        .subcommand(ConfigCommand::cmd())
         .subcommand(NewNote::cmd())
         .subcommand(ListNotes::cmd())
+        .subcommand(ExportGraph::cmd())
+        .subcommand(ListOrphans::cmd())
+        .subcommand(Templates::cmd())
+        .subcommand(Vaults::cmd())
 }
 
 #[tracing::instrument]
@@ -44,6 +62,18 @@ fn main() -> anyhow::Result<()> {
 
     let matches = cmd().get_matches();
 
+    // There is no config to read before `init` has created it, and `config` reads it itself
+    match matches.subcommand() {
+        Some(("init", sub_matches)) => {
+            let config_dir = get_config_path(matches.get_one::<String>("config-path"))?;
+            return Init::try_new(config_dir, sub_matches)?.run();
+        }
+        Some(("config", sub_matches)) => {
+            return ConfigCommand::try_new(&matches, sub_matches)?.run()
+        }
+        _ => {}
+    }
+
     let config = Config::try_new(&matches)?;
 
     match matches.subcommand() {
@@ -52,6 +82,10 @@ fn main() -> anyhow::Result<()> {
             NewNote::try_new(&config, sub_matches)?.write()?.display()
         ),
         Some(("ls", _)) => ListNotes::new(&config).run(),
+        Some(("graph", sub_matches)) => ExportGraph::try_new(&config, sub_matches)?.run()?,
+        Some(("orphans", sub_matches)) => ListOrphans::try_new(&config, sub_matches)?.run()?,
+        Some(("templates", sub_matches)) => Templates::try_new(&config, sub_matches)?.run()?,
+        Some(("vaults", sub_matches)) => Vaults::try_new(&config, sub_matches)?.run()?,
         Some(matching) => SubCommand::try_new(&config, matching)?.run()?,
         _ => unreachable!("Exhausted list of subcommands and subcommand_required prevents `None`"),
     }
@@ -0,0 +1,208 @@
+use anyhow::anyhow;
+use regex::Regex;
+use serde_json::Value;
+use std::sync::OnceLock;
+use time::{
+    format_description::well_known::Rfc3339, macros::format_description, Date, Duration,
+    OffsetDateTime, Time,
+};
+
+use crate::templating::{add_months, parse_date, parse_weekday};
+
+fn relative_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"^(?:(?P<in>in )?(?P<sign>[+-])?(?P<n>\d+) ?(?P<unit>[a-z]+)(?P<ago> ago)?)$")
+            .expect("valid regex")
+    })
+}
+
+fn error(input: &str) -> anyhow::Error {
+    anyhow!(
+        "Could not understand date {:?}. Ex. tomorrow, next tuesday 14:00, 2024-03-01 or -2d",
+        input
+    )
+}
+
+/// Parses dates like `tomorrow`, `next tuesday 14:00`, `2024-03-01`, `-2d` or `3 weeks ago`
+/// relative to `now`. The time of day is kept from `now` unless one is given.
+pub fn parse_natural_date(input: &str, now: OffsetDateTime) -> anyhow::Result<OffsetDateTime> {
+    if let Ok(dt) = OffsetDateTime::parse(input.trim(), &Rfc3339) {
+        return Ok(dt);
+    }
+
+    // Plain dates are handled below so they keep the time of `now`
+    let is_plain_date =
+        Date::parse(input.trim(), format_description!("[year]-[month]-[day]")).is_ok();
+    if let Some(dt) =
+        parse_date(&Value::String(input.trim().to_string())).filter(|_| !is_plain_date)
+    {
+        return Ok(dt.assume_offset(now.offset()));
+    }
+
+    let normalized = input.trim().to_lowercase();
+    let mut words: Vec<&str> = normalized.split_whitespace().collect();
+
+    let time = match words.last().map(|w| parse_time(w)) {
+        Some(Some(time)) => {
+            words.pop();
+            if words.last() == Some(&"at") {
+                words.pop();
+            }
+            Some(time)
+        }
+        _ => None,
+    };
+
+    let day = parse_day(&words.join(" "), now).ok_or_else(|| error(input))?;
+
+    Ok(match time {
+        Some(time) => day.replace_time(time),
+        None => day,
+    })
+}
+
+fn parse_time(word: &str) -> Option<Time> {
+    Time::parse(word, format_description!("[hour padding:none]:[minute]"))
+        .or_else(|_| {
+            Time::parse(
+                word,
+                format_description!("[hour padding:none]:[minute]:[second]"),
+            )
+        })
+        .ok()
+}
+
+fn parse_day(input: &str, now: OffsetDateTime) -> Option<OffsetDateTime> {
+    match input {
+        "" | "now" | "today" => return Some(now),
+        "tomorrow" => return now.checked_add(Duration::DAY),
+        "yesterday" => return now.checked_sub(Duration::DAY),
+        _ => {}
+    }
+
+    if let Some(captures) = relative_regex().captures(input) {
+        let ago = captures.name("ago").is_some();
+        let explicit_in = captures.name("in").is_some();
+        let sign = captures.name("sign").map(|s| s.as_str());
+        if ago && (explicit_in || sign.is_some()) {
+            return None;
+        }
+        let n: i64 = captures["n"].parse().ok()?;
+        let n = if ago || sign == Some("-") { -n } else { n };
+        return shift(now, n, &captures["unit"]);
+    }
+
+    let (direction, weekday) = match input.split_once(' ') {
+        Some((direction @ ("next" | "last" | "this"), weekday)) => (direction, weekday),
+        _ => ("this", input),
+    };
+    if let Some(weekday) = parse_weekday(weekday) {
+        let days = weekday.number_days_from_monday() as i64
+            - now.weekday().number_days_from_monday() as i64;
+        let days = match direction {
+            "next" if days <= 0 => days + 7,
+            "last" if days >= 0 => days - 7,
+            "this" if days < 0 => days + 7,
+            _ => days,
+        };
+        return now.checked_add(Duration::days(days));
+    }
+
+    Date::parse(input, format_description!("[year]-[month]-[day]"))
+        .ok()
+        .map(|date| now.replace_date(date))
+}
+
+fn shift(now: OffsetDateTime, n: i64, unit: &str) -> Option<OffsetDateTime> {
+    let months = |months: i64| {
+        add_months(time::PrimitiveDateTime::new(now.date(), now.time()), months)
+            .map(|dt| dt.assume_offset(now.offset()))
+    };
+
+    match unit {
+        "h" | "hour" | "hours" => now.checked_add(Duration::hours(n)),
+        "d" | "day" | "days" => now.checked_add(Duration::days(n)),
+        "w" | "week" | "weeks" => now.checked_add(Duration::weeks(n)),
+        "m" | "month" | "months" => months(n),
+        "y" | "year" | "years" => months(n * 12),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use time::macros::datetime;
+
+    // A Wednesday
+    static NOW: OffsetDateTime = datetime!(2024-05-15 10:30:00 +01:00);
+
+    fn parse(input: &str) -> OffsetDateTime {
+        parse_natural_date(input, NOW).unwrap()
+    }
+
+    #[test]
+    fn parses_named_days() {
+        assert_eq!(NOW, parse("today"));
+        assert_eq!(datetime!(2024-05-16 10:30:00 +01:00), parse("Tomorrow"));
+        assert_eq!(datetime!(2024-05-14 10:30:00 +01:00), parse("yesterday"));
+    }
+
+    #[test]
+    fn parses_weekdays() {
+        assert_eq!(
+            datetime!(2024-05-21 14:00:00 +01:00),
+            parse("next tuesday 14:00")
+        );
+        assert_eq!(
+            datetime!(2024-05-22 10:30:00 +01:00),
+            parse("next wednesday")
+        );
+        assert_eq!(datetime!(2024-05-15 10:30:00 +01:00), parse("wednesday"));
+        assert_eq!(datetime!(2024-05-17 10:30:00 +01:00), parse("fri"));
+        assert_eq!(
+            datetime!(2024-05-13 09:00:00 +01:00),
+            parse("last monday at 9:00")
+        );
+    }
+
+    #[test]
+    fn parses_relative_offsets() {
+        assert_eq!(datetime!(2024-05-13 10:30:00 +01:00), parse("-2d"));
+        assert_eq!(datetime!(2024-05-29 10:30:00 +01:00), parse("+2w"));
+        assert_eq!(datetime!(2024-06-15 10:30:00 +01:00), parse("1m"));
+        assert_eq!(datetime!(2024-05-18 10:30:00 +01:00), parse("in 3 days"));
+        assert_eq!(datetime!(2023-05-15 10:30:00 +01:00), parse("1 year ago"));
+    }
+
+    #[test]
+    fn parses_absolute_dates() {
+        assert_eq!(datetime!(2024-03-01 10:30:00 +01:00), parse("2024-03-01"));
+        assert_eq!(
+            datetime!(2024-03-01 08:15:00 +01:00),
+            parse("2024-03-01 8:15")
+        );
+        assert_eq!(
+            datetime!(2024-03-01 16:00:00 +01:00),
+            parse("2024-03-01T16:00:00")
+        );
+        assert_eq!(
+            datetime!(2024-03-01 10:00:00 +05:00),
+            parse("2024-03-01T10:00:00+05:00")
+        );
+        assert_eq!(
+            datetime!(2024-03-01 10:00:00 UTC),
+            parse("2024-03-01T10:00:00Z")
+        );
+    }
+
+    #[test]
+    fn fails_on_unknown_dates() {
+        let e = parse_natural_date("the day after never", NOW).unwrap_err();
+        assert!(e.to_string().contains("Could not understand date"));
+        assert!(parse_natural_date("in 2 days ago", NOW).is_err());
+        let e = parse_natural_date("5", NOW).unwrap_err();
+        assert!(e.to_string().contains("Could not understand date"));
+    }
+}
@@ -0,0 +1,518 @@
+use anyhow::Context;
+use regex::Regex;
+use serde::Serialize;
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::{Component, Path, PathBuf},
+    sync::OnceLock,
+};
+use walkdir::WalkDir;
+
+use crate::config::Config;
+
+fn wiki_link_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(!?)\[\[([^\]|#]*)(?:#([^\]|]*))?(?:\|[^\]]*)?\]\]").expect("valid regex")
+    })
+}
+
+fn markdown_link_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(!?)\[[^\]]*\]\(([^)\s#]+)(?:#([^)\s]*))?\)").expect("valid regex")
+    })
+}
+
+fn tag_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?:^|\s)#([\w][\w/-]*)").expect("valid regex"))
+}
+
+/// A `[[wiki link]]`, `![[embed]]` or relative markdown link found in a note.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Link {
+    pub target: String,
+    pub heading: Option<String>,
+    pub embed: bool,
+}
+
+/// A single note file in `notes_dir`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Note {
+    /// Path relative to `notes_dir` without extension. Ex. `projects/roadmap`
+    pub name: String,
+    pub path: PathBuf,
+    pub title: String,
+    pub tags: Vec<String>,
+    /// Other names the note can be linked by, from the `aliases` front matter list.
+    pub aliases: Vec<String>,
+    pub front_matter: serde_json::Value,
+    #[serde(skip)]
+    pub links: Vec<Link>,
+}
+
+impl Note {
+    pub fn parse(name: &str, path: &Path, contents: &str) -> Note {
+        let (front_matter, body) = split_front_matter(contents);
+        let front_matter = front_matter
+            .and_then(
+                |yaml| match serde_yaml::from_str::<serde_json::Value>(yaml) {
+                    Ok(value) if value.is_object() => Some(value),
+                    Ok(_) => None,
+                    Err(e) => {
+                        tracing::warn!("Could not parse front matter in {:?}: {}", path, e);
+                        None
+                    }
+                },
+            )
+            .unwrap_or_else(|| serde_json::Value::Object(Default::default()));
+
+        let text = strip_code_blocks(body);
+        let stem = name.rsplit('/').next().unwrap_or(name);
+
+        let title = front_matter
+            .get("title")
+            .and_then(|t| t.as_str())
+            .map(str::to_string)
+            .or_else(|| {
+                text.lines()
+                    .find_map(|l| l.strip_prefix("# "))
+                    .map(|t| t.trim().to_string())
+            })
+            .unwrap_or_else(|| stem.to_string());
+
+        let mut tags: BTreeSet<String> = string_list(front_matter.get("tags"))
+            .into_iter()
+            .map(|t| t.trim_start_matches('#').to_string())
+            .filter(|t| !t.is_empty())
+            .collect();
+        tags.extend(
+            tag_regex()
+                .captures_iter(&text)
+                .map(|c| c[1].trim_end_matches('/').to_string()),
+        );
+
+        Note {
+            name: name.to_string(),
+            path: path.to_path_buf(),
+            title,
+            tags: tags.into_iter().collect(),
+            aliases: string_list(front_matter.get("aliases")),
+            front_matter,
+            links: parse_links(&text),
+        }
+    }
+}
+
+/// Splits a leading `---` delimited yaml block from the rest of the note.
+pub fn split_front_matter(contents: &str) -> (Option<&str>, &str) {
+    let Some(rest) = contents
+        .strip_prefix("---\n")
+        .or_else(|| contents.strip_prefix("---\r\n"))
+    else {
+        return (None, contents);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+
+    (None, contents)
+}
+
+/// The part of `body` under `heading`, from the heading line up to the next heading of the same
+/// or a higher level. Headings are matched ignoring case.
+pub fn section<'a>(body: &'a str, heading: &str) -> Option<&'a str> {
+    let level_of = |line: &str| {
+        let level = line.chars().take_while(|c| *c == '#').count();
+        (level > 0 && line[level..].starts_with(' ')).then_some(level)
+    };
+
+    let mut start = None;
+    let mut offset = 0;
+    let mut in_code = false;
+    for line in body.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+        }
+        if let Some(level) = level_of(line).filter(|_| !in_code) {
+            match start {
+                Some((start, start_level)) if level <= start_level => {
+                    return Some(&body[start..offset]);
+                }
+                None if line[level..].trim().eq_ignore_ascii_case(heading.trim()) => {
+                    start = Some((offset, level));
+                }
+                _ => {}
+            }
+        }
+        offset += line.len();
+    }
+
+    start.map(|(start, _)| &body[start..])
+}
+
+/// Replaces every `![[embed]]` in `text` with what `replace` returns for it, keeping embeds it
+/// returns `None` for.
+pub fn replace_embeds<E>(
+    text: &str,
+    mut replace: impl FnMut(&Link) -> Result<Option<String>, E>,
+) -> Result<String, E> {
+    let mut out = String::new();
+    let mut last = 0;
+    for captures in wiki_link_regex().captures_iter(text) {
+        let whole = captures.get(0).expect("group 0 always matches");
+        if &captures[1] != "!" || captures[2].trim().is_empty() {
+            continue;
+        }
+        let link = Link {
+            target: captures[2].trim().to_string(),
+            heading: captures.get(3).map(|h| h.as_str().trim().to_string()),
+            embed: true,
+        };
+        if let Some(replacement) = replace(&link)? {
+            out.push_str(&text[last..whole.start()]);
+            out.push_str(&replacement);
+            last = whole.end();
+        }
+    }
+    out.push_str(&text[last..]);
+
+    Ok(out)
+}
+
+/// Reads a front matter value that is either a list or a comma separated string.
+pub fn string_list(value: Option<&serde_json::Value>) -> Vec<String> {
+    match value {
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .filter_map(|i| i.as_str())
+            .map(|i| i.trim().to_string())
+            .collect(),
+        Some(serde_json::Value::String(s)) => s
+            .split(',')
+            .map(|i| i.trim().to_string())
+            .filter(|i| !i.is_empty())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn strip_code_blocks(body: &str) -> String {
+    let mut in_code = false;
+    body.lines()
+        .filter(|line| {
+            if line.trim_start().starts_with("```") {
+                in_code = !in_code;
+                return false;
+            }
+            !in_code
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn parse_links(text: &str) -> Vec<Link> {
+    let mut links: Vec<(usize, Link)> = wiki_link_regex()
+        .captures_iter(text)
+        .filter(|c| !c[2].trim().is_empty())
+        .map(|c| {
+            (
+                c.get(0).map(|m| m.start()).unwrap_or_default(),
+                Link {
+                    target: c[2].trim().to_string(),
+                    heading: c.get(3).map(|h| h.as_str().trim().to_string()),
+                    embed: &c[1] == "!",
+                },
+            )
+        })
+        .collect();
+
+    links.extend(
+        markdown_link_regex()
+            .captures_iter(text)
+            .filter(|c| !c[2].contains("://") && !c[2].starts_with("mailto:"))
+            .map(|c| {
+                (
+                    c.get(0).map(|m| m.start()).unwrap_or_default(),
+                    Link {
+                        target: c[2].to_string(),
+                        heading: c.get(3).map(|h| h.as_str().to_string()),
+                        embed: &c[1] == "!",
+                    },
+                )
+            }),
+    );
+
+    links.sort_by_key(|(position, _)| *position);
+    links.into_iter().map(|(_, link)| link).collect()
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// All notes in `notes_dir`, indexed for link resolution.
+#[derive(Debug, Default)]
+pub struct Notes {
+    notes: Vec<Note>,
+    by_name: HashMap<String, usize>,
+    by_stem: HashMap<String, usize>,
+    by_alias: HashMap<String, usize>,
+    /// Stripped from link targets like `roadmap.md`
+    note_file_type: String,
+}
+
+impl Notes {
+    #[tracing::instrument]
+    pub fn load(config: &Config) -> anyhow::Result<Notes> {
+        let mut notes = vec![];
+
+        for entry in WalkDir::new(&config.notes_dir)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+        {
+            let entry = entry
+                .with_context(|| format!("Could not read notes dir {:?}", config.notes_dir))?;
+            let path = entry.path();
+            if !entry.file_type().is_file()
+                || path.extension().map(|e| e.to_string_lossy())
+                    != Some(config.note_file_type.as_str().into())
+            {
+                continue;
+            }
+
+            let name = path
+                .strip_prefix(&config.notes_dir)
+                .unwrap_or(path)
+                .with_extension("")
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let contents = fs::read_to_string(path)
+                .with_context(|| format!("Could not read note {:?}", path))?;
+
+            notes.push(Note::parse(&name, path, &contents));
+        }
+
+        Ok(Notes {
+            note_file_type: config.note_file_type.clone(),
+            ..Notes::from_notes(notes)
+        })
+    }
+
+    /// Indexes `notes` with the default `note_file_type`.
+    pub fn from_notes(notes: Vec<Note>) -> Notes {
+        let mut by_name = HashMap::new();
+        let mut by_stem = HashMap::new();
+        let mut by_alias = HashMap::new();
+        for (i, note) in notes.iter().enumerate() {
+            by_name.insert(normalize(&note.name), i);
+            let stem = note.name.rsplit('/').next().unwrap_or(&note.name);
+            by_stem.entry(normalize(stem)).or_insert(i);
+            for alias in &note.aliases {
+                by_alias.entry(normalize(alias)).or_insert(i);
+            }
+        }
+
+        Notes {
+            notes,
+            by_name,
+            by_stem,
+            by_alias,
+            note_file_type: Config::default().note_file_type,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Note> {
+        self.notes.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    pub fn index_of(&self, note: &Note) -> Option<usize> {
+        self.by_name.get(&normalize(&note.name)).copied()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Note> {
+        self.notes.get(index)
+    }
+
+    /// Finds a note by name, path relative to `notes_dir`, file stem or alias.
+    pub fn resolve(&self, target: &str) -> Option<&Note> {
+        self.resolve_index(target).map(|i| &self.notes[i])
+    }
+
+    fn resolve_index(&self, target: &str) -> Option<usize> {
+        let target = normalize(target);
+        let target = target.strip_prefix("./").unwrap_or(&target);
+        let target = Path::new(target)
+            .extension()
+            .filter(|e| *e == self.note_file_type.as_str())
+            .map(|_| &target[..target.len() - self.note_file_type.len() - 1])
+            .unwrap_or(target);
+
+        self.by_name
+            .get(target)
+            .or_else(|| self.by_stem.get(target))
+            .or_else(|| self.by_alias.get(target))
+            .copied()
+    }
+
+    /// Resolves a link found in `from`. Markdown links are relative to the linking note.
+    pub fn resolve_link(&self, from: &Note, link: &Link) -> Option<usize> {
+        if link.target.contains('/') {
+            let dir = Path::new(&from.name).parent().unwrap_or(Path::new(""));
+            let mut joined = PathBuf::new();
+            for component in dir.join(&link.target).components() {
+                match component {
+                    Component::ParentDir => {
+                        joined.pop();
+                    }
+                    Component::Normal(c) => joined.push(c),
+                    _ => {}
+                }
+            }
+            if let Some(i) = self.resolve_index(&joined.to_string_lossy()) {
+                return Some(i);
+            }
+        }
+
+        self.resolve_index(&link.target)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_front_matter_tags_title_and_links() {
+        let note = Note::parse(
+            "projects/roadmap",
+            Path::new("/tmp/projects/roadmap.md"),
+            "---\ntitle: Roadmap\ntags: [planning, q1]\n---\n# Ignored heading\nSee [[Goals#Now|goals]] and ![[diagram]]\nAlso [old](../archive/old.md) #review\n```\n[[not-a-link]] #nope\n```\n",
+        );
+
+        assert_eq!("Roadmap", note.title);
+        assert_eq!(vec!["planning", "q1", "review"], note.tags);
+        assert_eq!(
+            vec![
+                Link {
+                    target: "Goals".to_string(),
+                    heading: Some("Now".to_string()),
+                    embed: false
+                },
+                Link {
+                    target: "diagram".to_string(),
+                    heading: None,
+                    embed: true
+                },
+                Link {
+                    target: "../archive/old.md".to_string(),
+                    heading: None,
+                    embed: false
+                },
+            ],
+            note.links
+        );
+    }
+
+    #[test]
+    fn falls_back_to_heading_and_file_stem_for_title() {
+        let note = Note::parse("a/b", Path::new("a/b.md"), "# Heading\ntext");
+        assert_eq!("Heading", note.title);
+
+        let note = Note::parse("a/b", Path::new("a/b.md"), "text");
+        assert_eq!("b", note.title);
+    }
+
+    #[test]
+    fn resolves_links_by_name_stem_and_relative_path() {
+        let notes = Notes::from_notes(vec![
+            Note::parse("projects/roadmap", Path::new("projects/roadmap.md"), ""),
+            Note::parse("archive/old", Path::new("archive/old.md"), ""),
+        ]);
+        let from = notes.get(0).unwrap().clone();
+
+        let link = |target: &str| Link {
+            target: target.to_string(),
+            heading: None,
+            embed: false,
+        };
+
+        assert_eq!(Some(0), notes.resolve_link(&from, &link("Roadmap")));
+        assert_eq!(
+            Some(0),
+            notes.resolve_link(&from, &link("projects/roadmap"))
+        );
+        assert_eq!(
+            Some(1),
+            notes.resolve_link(&from, &link("../archive/old.md"))
+        );
+        assert_eq!(None, notes.resolve_link(&from, &link("missing")));
+    }
+
+    #[test]
+    fn resolves_links_with_the_note_file_type() {
+        let notes = Notes {
+            note_file_type: "txt".to_string(),
+            ..Notes::from_notes(vec![Note::parse("todo", Path::new("todo.txt"), "")])
+        };
+
+        assert_eq!("todo", notes.resolve("todo.txt").unwrap().name);
+        assert!(notes.resolve("todo.md").is_none());
+    }
+
+    #[test]
+    fn finds_heading_sections() {
+        let body = "# Roadmap\nIntro\n## Goals\nShip it\n### Details\n```\n# not a heading\n```\n## Risks\nNone\n";
+
+        assert_eq!(
+            Some("## Goals\nShip it\n### Details\n```\n# not a heading\n```\n"),
+            section(body, "goals")
+        );
+        assert_eq!(Some("## Risks\nNone\n"), section(body, "Risks"));
+        assert_eq!(Some(body), section(body, "Roadmap"));
+        assert_eq!(None, section(body, "Missing"));
+    }
+
+    #[test]
+    fn replaces_embeds() {
+        let replaced =
+            replace_embeds::<()>("See [[goals]]\n![[goals#Now]] and ![[missing]]", |link| {
+                Ok((link.target == "goals")
+                    .then(|| format!("<{}>", link.heading.as_deref().unwrap_or_default())))
+            })
+            .unwrap();
+
+        assert_eq!("See [[goals]]\n<Now> and ![[missing]]", replaced);
+    }
+
+    #[test]
+    fn resolves_links_by_alias() {
+        let notes = Notes::from_notes(vec![
+            Note::parse(
+                "kubernetes",
+                Path::new("kubernetes.md"),
+                "---\naliases: [K8s, kube]\n---\n",
+            ),
+            Note::parse("kube", Path::new("kube.md"), ""),
+        ]);
+
+        assert_eq!("kubernetes", notes.resolve("k8s").unwrap().name);
+        let link = &parse_links("See [[K8s]]")[0];
+        assert_eq!(Some(0), notes.resolve_link(notes.get(1).unwrap(), link));
+        assert_eq!("kube", notes.resolve("kube").unwrap().name);
+    }
+}
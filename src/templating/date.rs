@@ -0,0 +1,606 @@
+use handlebars::{Handlebars, Helper, RenderError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::macros::format_description;
+
+use super::{FnHelper, HelperFn};
+use time::{
+    format_description::{
+        parse,
+        well_known::{Iso8601, Rfc2822, Rfc3339},
+        BorrowedFormatItem,
+    },
+    util, Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Weekday,
+};
+
+pub static DEFAULT_DATE_FORMAT: &str = "[year]-[month]-[day]";
+
+static SUPPORTED_FORMATS: &str = "Iso8601, Rfc3339, Rfc2822, \
+    [year]-[month]-[day] [hour]:[minute]:[second], \
+    [year]-[month]-[day] [hour]:[minute], \
+    [year]-[month]-[day] [hour], [year]-[month]-[day] or a Unix timestamp";
+
+/// Reference date passed to templates as `now`. Ex. `{{now.year}}`, `{{date now}}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Now {
+    /// Rfc3339 timestamp including the configured offset
+    pub iso: String,
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub ordinal: u16,
+    pub iso_week: u8,
+    pub weekday: String,
+    pub unix: i64,
+    pub offset: String,
+}
+
+impl From<OffsetDateTime> for Now {
+    fn from(dt: OffsetDateTime) -> Self {
+        let dt = dt.replace_nanosecond(0).unwrap_or(dt);
+        Now {
+            iso: dt.format(&Rfc3339).unwrap_or_else(|_| dt.to_string()),
+            year: dt.year(),
+            month: dt.month().into(),
+            day: dt.day(),
+            hour: dt.hour(),
+            minute: dt.minute(),
+            second: dt.second(),
+            ordinal: dt.ordinal(),
+            iso_week: dt.iso_week(),
+            weekday: dt.weekday().to_string(),
+            unix: dt.unix_timestamp(),
+            offset: dt
+                .offset()
+                .format(format_description!(
+                    "[offset_hour sign:mandatory]:[offset_minute]"
+                ))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Parses the date formats accepted by the `date` helper.
+pub fn parse_date(value: &Value) -> Option<PrimitiveDateTime> {
+    match value {
+        Value::Number(n) => n.as_i64().and_then(from_unix_timestamp),
+        Value::String(s) => parse_date_str(s.trim()),
+        Value::Object(o) => o.get("iso").and_then(parse_date),
+        _ => None,
+    }
+}
+
+fn from_unix_timestamp(timestamp: i64) -> Option<PrimitiveDateTime> {
+    OffsetDateTime::from_unix_timestamp(timestamp)
+        .ok()
+        .map(|d| PrimitiveDateTime::new(d.date(), d.time()))
+}
+
+fn parse_date_str(dt: &str) -> Option<PrimitiveDateTime> {
+    [
+        PrimitiveDateTime::parse(dt, &Iso8601::DEFAULT),
+        PrimitiveDateTime::parse(dt, &Rfc3339),
+        PrimitiveDateTime::parse(dt, &Rfc2822),
+        PrimitiveDateTime::parse(
+            dt,
+            &format_description!("[year]-[month]-[day] [hour]:[minute]:[second]"),
+        ),
+        PrimitiveDateTime::parse(
+            dt,
+            &format_description!("[year]-[month]-[day] [hour]:[minute]"),
+        ),
+        PrimitiveDateTime::parse(dt, &format_description!("[year]-[month]-[day] [hour]")),
+        // `OffsetDateTime`'s display format
+        PrimitiveDateTime::parse(
+            dt,
+            &format_description!(
+                "[year]-[month]-[day] [hour padding:none]:[minute]:[second].[subsecond] [offset_hour sign:mandatory]:[offset_minute]:[offset_second]"
+            ),
+        ),
+    ]
+    .into_iter()
+    .find_map(|d| d.ok())
+    .or_else(|| {
+        Date::parse(dt, &format_description!("[year]-[month]-[day]"))
+            .ok()
+            .map(|d| d.midnight())
+    })
+}
+
+pub fn register_date_helpers(handlebars: &mut Handlebars) {
+    let helpers: [(&str, HelperFn); 9] = [
+        ("date", date),
+        ("date_add", date_add),
+        ("date_sub", date_sub),
+        ("start_of", start_of),
+        ("end_of", end_of),
+        ("iso_week", iso_week),
+        ("weekday", weekday),
+        ("next_weekday", next_weekday),
+        ("previous_weekday", previous_weekday),
+    ];
+    for (name, helper) in helpers {
+        handlebars.register_helper(name, Box::new(FnHelper(helper)));
+    }
+}
+
+pub(super) fn date_param(h: &Helper) -> Result<PrimitiveDateTime, RenderError> {
+    let param = h
+        .param(0)
+        .ok_or_else(|| RenderError::new(format!("Helper {}: missing date parameter", h.name())))?;
+
+    parse_date(param.value()).ok_or_else(|| {
+        RenderError::new(format!(
+            "Helper {}: could not parse {} from {}. Supported formats: {}",
+            h.name(),
+            param.value(),
+            param
+                .relative_path()
+                .map(String::as_str)
+                .unwrap_or("parameter"),
+            SUPPORTED_FORMATS
+        ))
+    })
+}
+
+fn string_param<'a>(h: &'a Helper, index: usize, what: &str) -> Result<&'a str, RenderError> {
+    h.param(index)
+        .and_then(|p| p.value().as_str())
+        .ok_or_else(|| RenderError::new(format!("Helper {}: missing {}", h.name(), what)))
+}
+
+fn to_value(dt: PrimitiveDateTime) -> Value {
+    Value::String(
+        dt.format(format_description!(
+            "[year]-[month]-[day]T[hour]:[minute]:[second]"
+        ))
+        .unwrap_or_else(|_| dt.to_string()),
+    )
+}
+
+/// The `fmt` hash parameter, defaulting to [DEFAULT_DATE_FORMAT].
+pub(super) fn fmt_param<'a>(
+    h: &'a Helper,
+) -> Result<(&'a str, Vec<BorrowedFormatItem<'a>>), RenderError> {
+    let fmt = match h.hash_get("fmt").map(|f| f.value()) {
+        None => DEFAULT_DATE_FORMAT,
+        Some(Value::String(fmt)) => fmt.as_str(),
+        Some(other) => {
+            return Err(RenderError::new(format!(
+                "Helper {}: fmt must be a string, got {}",
+                h.name(),
+                other
+            )))
+        }
+    };
+
+    let description = parse(fmt).map_err(|e| {
+        RenderError::new(format!(
+            "Helper {}: invalid fmt \"{}\": {}. See https://time-rs.github.io/book/api/format-description.html",
+            h.name(), fmt, e
+        ))
+    })?;
+
+    Ok((fmt, description))
+}
+
+/// `{{date value fmt="[day]/[month]/[year]"}}`
+fn date(h: &Helper) -> Result<Value, RenderError> {
+    let (fmt, description) = fmt_param(h)?;
+    let date = date_param(h)?;
+
+    let formatted = date.format(&description).map_err(|e| {
+        RenderError::new(format!(
+            "Helper date: could not format with \"{}\": {}",
+            fmt, e
+        ))
+    })?;
+
+    Ok(Value::String(formatted))
+}
+
+pub fn add_months(dt: PrimitiveDateTime, months: i64) -> Option<PrimitiveDateTime> {
+    let total = dt.year() as i64 * 12 + u8::from(dt.month()) as i64 - 1 + months;
+    let year = i32::try_from(total.div_euclid(12)).ok()?;
+    let month = Month::try_from(total.rem_euclid(12) as u8 + 1).ok()?;
+    let day = dt.day().min(util::days_in_year_month(year, month));
+
+    Date::from_calendar_date(year, month, day)
+        .ok()
+        .map(|d| d.with_time(dt.time()))
+}
+
+fn shift(h: &Helper, sign: i64) -> Result<Value, RenderError> {
+    let dt = date_param(h)?;
+
+    let amount = |key: &str| -> Result<i64, RenderError> {
+        match h.hash_get(key).map(|v| v.value()) {
+            None => Some(0),
+            Some(Value::Number(n)) => n.as_i64(),
+            Some(Value::String(s)) => s.trim().parse().ok(),
+            Some(_) => None,
+        }
+        .map(|n| n * sign)
+        .ok_or_else(|| {
+            RenderError::new(format!(
+                "Helper {}: {} must be a whole number",
+                h.name(),
+                key
+            ))
+        })
+    };
+
+    let months = amount("years")? * 12 + amount("months")?;
+    let duration = Duration::weeks(amount("weeks")?)
+        + Duration::days(amount("days")?)
+        + Duration::hours(amount("hours")?)
+        + Duration::minutes(amount("minutes")?)
+        + Duration::seconds(amount("seconds")?);
+
+    add_months(dt, months)
+        .and_then(|dt| dt.checked_add(duration))
+        .map(to_value)
+        .ok_or_else(|| RenderError::new(format!("Helper {}: date out of range", h.name())))
+}
+
+/// `{{date_add now days=1}}`. Accepts years, months, weeks, days, hours, minutes and seconds
+fn date_add(h: &Helper) -> Result<Value, RenderError> {
+    shift(h, 1)
+}
+
+/// `{{date_sub now weeks=1}}`. Accepts the same units as `date_add`
+fn date_sub(h: &Helper) -> Result<Value, RenderError> {
+    shift(h, -1)
+}
+
+fn period_start(dt: PrimitiveDateTime, unit: &str) -> Option<PrimitiveDateTime> {
+    let date = dt.date();
+    let start = match unit {
+        "day" => date,
+        "week" => date - Duration::days(date.weekday().number_days_from_monday().into()),
+        "month" => date.replace_day(1).ok()?,
+        "quarter" => {
+            let month = (u8::from(date.month()) - 1) / 3 * 3 + 1;
+            Date::from_calendar_date(date.year(), Month::try_from(month).ok()?, 1).ok()?
+        }
+        "year" => Date::from_calendar_date(date.year(), Month::January, 1).ok()?,
+        _ => return None,
+    };
+
+    Some(start.midnight())
+}
+
+fn unit_param<'a>(h: &'a Helper) -> Result<&'a str, RenderError> {
+    let unit = string_param(h, 1, "unit, one of day, week, month, quarter or year")?;
+    match unit {
+        "day" | "week" | "month" | "quarter" | "year" => Ok(unit),
+        _ => Err(RenderError::new(format!(
+            "Helper {}: unknown unit \"{}\", use day, week, month, quarter or year",
+            h.name(),
+            unit
+        ))),
+    }
+}
+
+/// `{{start_of now "week"}}`. Weeks start on Monday
+fn start_of(h: &Helper) -> Result<Value, RenderError> {
+    let dt = date_param(h)?;
+    period_start(dt, unit_param(h)?)
+        .map(to_value)
+        .ok_or_else(|| RenderError::new(format!("Helper {}: date out of range", h.name())))
+}
+
+/// `{{end_of now "month"}}`. The last second of the period
+fn end_of(h: &Helper) -> Result<Value, RenderError> {
+    let dt = date_param(h)?;
+    let unit = unit_param(h)?;
+
+    period_start(dt, unit)
+        .and_then(|start| match unit {
+            "day" => start.checked_add(Duration::DAY),
+            "week" => start.checked_add(Duration::WEEK),
+            "month" => add_months(start, 1),
+            "quarter" => add_months(start, 3),
+            _ => add_months(start, 12),
+        })
+        .and_then(|next| next.checked_sub(Duration::SECOND))
+        .map(to_value)
+        .ok_or_else(|| RenderError::new(format!("Helper {}: date out of range", h.name())))
+}
+
+/// `{{iso_week now}}`
+fn iso_week(h: &Helper) -> Result<Value, RenderError> {
+    Ok(Value::from(date_param(h)?.iso_week()))
+}
+
+/// `{{weekday now}}`. Ex. Monday
+fn weekday(h: &Helper) -> Result<Value, RenderError> {
+    Ok(Value::String(date_param(h)?.weekday().to_string()))
+}
+
+fn weekday_param(h: &Helper) -> Result<Weekday, RenderError> {
+    let name = string_param(h, 1, "weekday")?;
+    parse_weekday(name).ok_or_else(|| {
+        RenderError::new(format!("Helper {}: unknown weekday \"{}\"", h.name(), name))
+    })
+}
+
+/// Parses full or three letter weekday names. Ex. `friday`, `Fri`
+pub fn parse_weekday(name: &str) -> Option<Weekday> {
+    let name = name.trim().to_lowercase();
+    let mut weekday = Weekday::Monday;
+    for _ in 0..7 {
+        let full = weekday.to_string().to_lowercase();
+        if name.len() >= 3 && full.starts_with(&name) {
+            return Some(weekday);
+        }
+        weekday = weekday.next();
+    }
+    None
+}
+
+/// `{{next_weekday now "friday"}}`. Always after the given date
+fn next_weekday(h: &Helper) -> Result<Value, RenderError> {
+    let dt = date_param(h)?;
+    let weekday = weekday_param(h)?;
+    let days = (weekday.number_days_from_monday() as i64
+        - dt.weekday().number_days_from_monday() as i64)
+        .rem_euclid(7);
+
+    Ok(to_value(
+        dt + Duration::days(if days == 0 { 7 } else { days }),
+    ))
+}
+
+/// `{{previous_weekday now "monday"}}`. Always before the given date
+fn previous_weekday(h: &Helper) -> Result<Value, RenderError> {
+    let dt = date_param(h)?;
+    let weekday = weekday_param(h)?;
+    let days = (dt.weekday().number_days_from_monday() as i64
+        - weekday.number_days_from_monday() as i64)
+        .rem_euclid(7);
+
+    Ok(to_value(
+        dt - Duration::days(if days == 0 { 7 } else { days }),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use handlebars::Handlebars;
+    use serde_json::json;
+
+    fn render(template: &str, data: &Value) -> Result<String, RenderError> {
+        let mut handlebars = Handlebars::new();
+        register_date_helpers(&mut handlebars);
+        handlebars
+            .register_template_string("test", template)
+            .unwrap();
+        handlebars.render("test", data)
+    }
+
+    #[test_log::test]
+    fn can_parse_iso_date() {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_helper("date", Box::new(FnHelper(date)));
+
+        let data = json!({"timestamp": "2023-01-01T16:00"});
+        let t = handlebars
+            .render_template("{{date timestamp}}", &data)
+            .unwrap();
+        assert_eq!("2023-01-01", t);
+
+        let data = json!({"timestamp": "2023-01-01T16:00"});
+        let t = handlebars
+            .render_template("{{date timestamp}}", &data)
+            .unwrap();
+        assert_eq!("2023-01-01", t);
+    }
+
+    #[test_log::test]
+    fn can_parse_rfc3339_date() {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_helper("date", Box::new(FnHelper(date)));
+
+        let data = json!({"timestamp": "2023-01-01T16:00:00+02:00"});
+        let t = handlebars
+            .render_template("{{date timestamp}}", &data)
+            .unwrap();
+        assert_eq!("2023-01-01", t);
+    }
+
+    #[test_log::test]
+    fn can_parse_rfc2822_date() {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_helper("date", Box::new(FnHelper(date)));
+
+        let data = json!({"timestamp": "Mon, 1 Jan 2023 16:00:00 GMT"});
+        let t = handlebars
+            .render_template("{{date timestamp}}", &data)
+            .unwrap();
+        assert_eq!("2023-01-01", t);
+    }
+
+    #[test_log::test]
+    fn can_parse_custom_date() {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_helper("date", Box::new(FnHelper(date)));
+
+        let data = json!({"timestamp": "2023-01-01 16:00:00"});
+        let t = handlebars
+            .render_template("{{date timestamp}}", &data)
+            .unwrap();
+        assert_eq!("2023-01-01", t);
+    }
+
+    #[test_log::test]
+    fn can_parse_custom2_date() {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_helper("date", Box::new(FnHelper(date)));
+
+        let data = json!({"timestamp": "2023-01-01 16:00"});
+        let t = handlebars
+            .render_template("{{date timestamp}}", &data)
+            .unwrap();
+        assert_eq!("2023-01-01", t);
+    }
+
+    #[test_log::test]
+    fn can_parse_custom3_date() {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_helper("date", Box::new(FnHelper(date)));
+
+        let data = json!({"timestamp": "2023-01-01 16"});
+        let t = handlebars
+            .render_template("{{date timestamp}}", &data)
+            .unwrap();
+        assert_eq!("2023-01-01", t);
+    }
+
+    #[test_log::test]
+    fn can_parse_plain_date() {
+        let data = json!({"timestamp": "2023-01-01"});
+        assert_eq!(
+            "2023-01-01 00:00",
+            render(
+                "{{date timestamp fmt=\"[year]-[month]-[day] [hour]:[minute]\"}}",
+                &data
+            )
+            .unwrap()
+        );
+    }
+
+    #[test_log::test]
+    fn can_parse_offset_date_time_display_format() {
+        let data = json!({"timestamp": "2023-01-01 7:31:55.857480479 +00:00:00"});
+        assert_eq!("2023-01-01", render("{{date timestamp}}", &data).unwrap());
+    }
+
+    #[test_log::test]
+    fn can_parse_unix_timestamp() {
+        let data = json!({"number": 1672588800, "string": "1672588800"});
+        assert_eq!(
+            "2023-01-01 16:00",
+            render(
+                "{{date number fmt=\"[year]-[month]-[day] [hour]:[minute]\"}}",
+                &data
+            )
+            .unwrap()
+        );
+        // Digits in a string are more likely an id or a year than a timestamp
+        assert!(render("{{date string}}", &data).is_err());
+    }
+
+    #[test_log::test]
+    fn can_format_structured_now() {
+        let dt = time::macros::datetime!(2023-01-01 23:30:00 +01:00);
+        let data = json!({ "now": Now::from(dt) });
+
+        assert_eq!("2023-01-01", render("{{date now}}", &data).unwrap());
+        assert_eq!(
+            "2023-01-01T23:30:00+01:00 2023 52 Sunday",
+            render(
+                "{{now.iso}} {{now.year}} {{now.iso_week}} {{now.weekday}}",
+                &data
+            )
+            .unwrap()
+        );
+    }
+
+    #[test_log::test]
+    fn reports_unparsable_date() {
+        let data = json!({"timestamp": "yesterday-ish"});
+        let e = render("Created: {{date timestamp}}", &data).unwrap_err();
+
+        assert_eq!(Some("test".to_string()), e.template_name);
+        assert!(e.to_string().contains("\"yesterday-ish\""));
+        assert!(e.to_string().contains("timestamp"));
+        assert!(e.to_string().contains("Supported formats: Iso8601"));
+    }
+
+    #[test_log::test]
+    fn reports_invalid_format() {
+        let data = json!({"timestamp": "2023-01-01 16"});
+        let e = render("{{date timestamp fmt=\"[yer]\"}}", &data).unwrap_err();
+
+        assert!(e.to_string().contains("invalid fmt \"[yer]\""));
+    }
+
+    #[test_log::test]
+    fn can_add_and_subtract_dates() {
+        let data = json!({"now": "2024-01-31T10:00:00"});
+
+        assert_eq!(
+            "2024-02-01T10:00:00",
+            render("{{date_add now days=1}}", &data).unwrap()
+        );
+        assert_eq!(
+            "2024-02-29T10:00:00",
+            render("{{date_add now months=1}}", &data).unwrap()
+        );
+        assert_eq!(
+            "2024-01-30T10:00:00",
+            render("{{date_sub now days=1}}", &data).unwrap()
+        );
+        assert_eq!(
+            "2023-01-24T08:30:00",
+            render("{{date_sub now years=1 weeks=1 hours=1 minutes=30}}", &data).unwrap()
+        );
+        assert_eq!(
+            "30/01",
+            render(
+                "{{date (date_sub now days=1) fmt=\"[day]/[month]\"}}",
+                &data
+            )
+            .unwrap()
+        );
+    }
+
+    #[test_log::test]
+    fn can_get_start_and_end_of_periods() {
+        let data = json!({"now": "2024-05-15T10:00:00"});
+
+        let cases = [
+            ("day", "2024-05-15T00:00:00", "2024-05-15T23:59:59"),
+            ("week", "2024-05-13T00:00:00", "2024-05-19T23:59:59"),
+            ("month", "2024-05-01T00:00:00", "2024-05-31T23:59:59"),
+            ("quarter", "2024-04-01T00:00:00", "2024-06-30T23:59:59"),
+            ("year", "2024-01-01T00:00:00", "2024-12-31T23:59:59"),
+        ];
+        for (unit, start, end) in cases {
+            let template = format!("{{{{start_of now \"{unit}\"}}}} {{{{end_of now \"{unit}\"}}}}");
+            assert_eq!(
+                format!("{} {}", start, end),
+                render(&template, &data).unwrap()
+            );
+        }
+
+        assert!(render("{{start_of now \"fortnight\"}}", &data)
+            .unwrap_err()
+            .to_string()
+            .contains("unknown unit \"fortnight\""));
+    }
+
+    #[test_log::test]
+    fn can_get_weekdays_and_iso_weeks() {
+        // A Wednesday
+        let data = json!({"now": "2024-05-15T10:00:00"});
+
+        assert_eq!(
+            "20 Wednesday",
+            render("{{iso_week now}} {{weekday now}}", &data).unwrap()
+        );
+        assert_eq!(
+            "2024-05-17 2024-05-22 2024-05-13 2024-05-08",
+            render(
+                "{{date (next_weekday now \"friday\")}} {{date (next_weekday now \"Wed\")}} {{date (previous_weekday now \"monday\")}} {{date (previous_weekday now \"wednesday\")}}",
+                &data
+            )
+            .unwrap()
+        );
+    }
+}
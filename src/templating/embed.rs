@@ -0,0 +1,156 @@
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, RenderError,
+};
+use std::{fs, sync::Arc};
+
+use super::LazyNotes;
+use crate::notes::{replace_embeds, section, split_front_matter, Note, Notes};
+
+/// Inserts the body of a note in `notes_dir`, ex. `{{embed "roadmap"}}`, or a single section of
+/// it, ex. `{{embed "roadmap#Goals"}}`. `![[embeds]]` in the embedded note are expanded too.
+///
+/// The output is not escaped, so it works the same with `{{embed}}` and `{{{embed}}}`.
+pub struct EmbedHelper {
+    notes: Arc<LazyNotes>,
+}
+
+impl EmbedHelper {
+    fn embed(
+        &self,
+        notes: &Notes,
+        note: &Note,
+        heading: Option<&str>,
+        stack: &mut Vec<String>,
+    ) -> Result<String, String> {
+        let key = match heading {
+            Some(heading) => format!("{}#{}", note.name, heading),
+            None => note.name.clone(),
+        };
+        if stack.contains(&key) {
+            stack.push(key);
+            return Err(format!("cycle detected: {}", stack.join(" -> ")));
+        }
+
+        let contents = fs::read_to_string(&note.path)
+            .map_err(|e| format!("could not read {:?}: {}", note.path, e))?;
+        let (_, body) = split_front_matter(&contents);
+        let body = match heading {
+            Some(heading) => section(body, heading)
+                .ok_or_else(|| format!("{} has no heading {}", note.name, heading))?,
+            None => body,
+        };
+
+        stack.push(key);
+        let embedded = replace_embeds(body, |link| {
+            notes
+                .resolve_link(note, link)
+                .and_then(|i| notes.get(i))
+                .map(|target| self.embed(notes, target, link.heading.as_deref(), stack))
+                .transpose()
+        })?;
+        stack.pop();
+
+        Ok(embedded.trim_end().to_string())
+    }
+}
+
+impl HelperDef for EmbedHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let error = |e: String| RenderError::new(format!("Helper {}: {}", h.name(), e));
+
+        let target = h
+            .param(0)
+            .and_then(|p| p.value().as_str())
+            .ok_or_else(|| error("missing note. Ex. {{embed \"roadmap#Goals\"}}".to_string()))?;
+        let (name, heading) = match target.split_once('#') {
+            Some((name, heading)) => (name, Some(heading)),
+            None => (target, None),
+        };
+
+        let notes = self.notes.get()?;
+        let note = notes
+            .resolve(name)
+            .ok_or_else(|| error(format!("could not find note {}", name)))?;
+        let embedded = self
+            .embed(notes, note, heading, &mut vec![])
+            .map_err(error)?;
+
+        out.write(&embedded)?;
+        Ok(())
+    }
+}
+
+pub fn register_embed_helper(handlebars: &mut Handlebars, notes: Arc<LazyNotes>) {
+    handlebars.register_helper("embed", Box::new(EmbedHelper { notes }));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::Config;
+    use crate::templating::test_utils::notes_dir;
+    use serde_json::json;
+
+    fn render(config: &Config, template: &str) -> Result<String, RenderError> {
+        let mut handlebars = Handlebars::new();
+        register_embed_helper(&mut handlebars, Arc::new(LazyNotes::new(config)));
+        handlebars.render_template(template, &json!({}))
+    }
+
+    #[test]
+    fn embeds_notes_and_sections() {
+        let (dir, config) = notes_dir(&[
+            (
+                "projects/roadmap",
+                "---\ntitle: Roadmap\n---\n# Roadmap\n## Goals\nShip & ![[team#Members]]\n## Risks\nNone\n",
+            ),
+            ("team", "# Team\n## Members\n- Max\n## Other\n"),
+        ]);
+
+        assert_eq!(
+            "# Roadmap\n## Goals\nShip & ## Members\n- Max\n## Risks\nNone",
+            render(&config, "{{embed \"roadmap\"}}").unwrap()
+        );
+        assert_eq!(
+            "## Risks\nNone",
+            render(&config, "{{embed \"projects/roadmap#risks\"}}").unwrap()
+        );
+        assert!(render(&config, "{{embed \"missing\"}}")
+            .unwrap_err()
+            .to_string()
+            .contains("could not find note missing"));
+        assert!(render(&config, "{{embed \"team#Missing\"}}")
+            .unwrap_err()
+            .to_string()
+            .contains("team has no heading Missing"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn detects_cycles() {
+        let (dir, config) = notes_dir(&[
+            ("a", "A\n![[b]]\n"),
+            ("b", "B\n![[a]]\n"),
+            ("c", "![[c#Self]]\n# Self\n![[c#Self]]\n"),
+        ]);
+
+        assert!(render(&config, "{{embed \"a\"}}")
+            .unwrap_err()
+            .to_string()
+            .contains("cycle detected: a -> b -> a"));
+        assert!(render(&config, "{{embed \"c#Self\"}}")
+            .unwrap_err()
+            .to_string()
+            .contains("cycle detected: c#Self -> c#Self"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
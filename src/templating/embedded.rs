@@ -0,0 +1,51 @@
+/// Templates built into the binary, used when no templates directory has one with the same name.
+/// Starter templates can be copied into `templates_dir` with `templates export`.
+pub static EMBEDDED_TEMPLATES: &[(&str, &str)] = &[
+    ("daily.hbs", include_str!("starters/daily.hbs")),
+    ("decision.hbs", include_str!("starters/decision.hbs")),
+    ("empty.hbs", ""),
+    ("meeting.hbs", include_str!("starters/meeting.hbs")),
+    ("project.hbs", include_str!("starters/project.hbs")),
+    ("weekly.hbs", include_str!("starters/weekly.hbs")),
+    ("zettel.hbs", include_str!("starters/zettel.hbs")),
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::templating::{date::register_date_helpers, Now};
+    use handlebars::Handlebars;
+    use serde_json::json;
+    use time::macros::datetime;
+
+    #[test]
+    fn starters_render_in_strict_mode() {
+        let mut handlebars = Handlebars::new();
+        handlebars.set_strict_mode(true);
+        register_date_helpers(&mut handlebars);
+        let data = json!({
+            "name": "Kickoff",
+            "now": Now::from(datetime!(2024-03-06 09:30 +01:00)),
+        });
+
+        for (path, source) in EMBEDDED_TEMPLATES {
+            let output = handlebars
+                .render_template(source, &data)
+                .unwrap_or_else(|e| panic!("{} failed to render: {}", path, e));
+            assert!(
+                !output.starts_with('\n'),
+                "{} starts with a blank line",
+                path
+            );
+        }
+
+        let (_, weekly) = EMBEDDED_TEMPLATES
+            .iter()
+            .find(|(path, _)| *path == "weekly.hbs")
+            .unwrap();
+        assert!(handlebars
+            .render_template(weekly, &data)
+            .unwrap()
+            .starts_with("# Week 10, 2024\n2024-03-04 to 2024-03-10\n"));
+    }
+}
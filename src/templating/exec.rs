@@ -0,0 +1,203 @@
+use handlebars::{Context, Handlebars, Helper, HelperDef, RenderContext, RenderError, ScopedJson};
+use serde_json::Value;
+use std::{
+    io::Read,
+    path::PathBuf,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::config::Config;
+
+static POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Runs a command and renders its trimmed stdout, ex. `{{exec "git config user.name"}}` or
+/// `{{exec "git" "config" "user.name"}}`. Only programs in `exec_allow_list` can be run, and
+/// only when `allow_exec` is set.
+pub struct ExecHelper {
+    enabled: bool,
+    allow_list: Vec<String>,
+    timeout: Duration,
+    dir: Option<PathBuf>,
+}
+
+impl ExecHelper {
+    pub fn new(config: &Config) -> ExecHelper {
+        ExecHelper {
+            enabled: config.allow_exec,
+            allow_list: config.exec_allow_list.clone(),
+            timeout: config.exec_timeout(),
+            dir: Some(config.notes_dir.clone()).filter(|d| d.is_dir()),
+        }
+    }
+
+    fn run(&self, args: &[String]) -> Result<String, String> {
+        let display = args.join(" ");
+        let Some((program, args)) = args.split_first() else {
+            return Err("missing command. Ex. {{exec \"git config user.name\"}}".to_string());
+        };
+        if !self.enabled {
+            return Err(format!(
+                "can not run `{}`, running commands is disabled. Set allow_exec = true in config.toml",
+                display
+            ));
+        }
+        if !self.allow_list.iter().any(|allowed| allowed == program) {
+            return Err(format!(
+                "can not run `{}`, {} is not in exec_allow_list",
+                display, program
+            ));
+        }
+
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(dir) = &self.dir {
+            command.current_dir(dir);
+        }
+
+        let mut child = command
+            .spawn()
+            .map_err(|e| format!("could not run `{}`: {}", display, e))?;
+        let stdout = read_in_background(child.stdout.take());
+        let stderr = read_in_background(child.stderr.take());
+
+        let started = Instant::now();
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if started.elapsed() >= self.timeout => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(format!(
+                        "`{}` timed out after {}ms",
+                        display,
+                        self.timeout.as_millis()
+                    ));
+                }
+                Ok(None) => thread::sleep(POLL_INTERVAL),
+                Err(e) => return Err(format!("could not wait for `{}`: {}", display, e)),
+            }
+        };
+
+        let stdout = stdout.join().unwrap_or_default();
+        if !status.success() {
+            let stderr = stderr.join().unwrap_or_default();
+            return Err(format!(
+                "`{}` failed with {}: {}",
+                display,
+                status,
+                stderr.trim()
+            ));
+        }
+
+        Ok(stdout.trim().to_string())
+    }
+}
+
+fn read_in_background<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut output = String::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_string(&mut output);
+        }
+        output
+    })
+}
+
+fn command_args(h: &Helper) -> Vec<String> {
+    let params: Vec<String> = h
+        .params()
+        .iter()
+        .map(|p| match p.value() {
+            Value::String(s) => s.clone(),
+            value => value.to_string(),
+        })
+        .collect();
+
+    match params.as_slice() {
+        [command] => command.split_whitespace().map(str::to_string).collect(),
+        _ => params,
+    }
+}
+
+impl HelperDef for ExecHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg, 'rc>, RenderError> {
+        self.run(&command_args(h))
+            .map(|output| ScopedJson::Derived(Value::String(output)))
+            .map_err(|e| RenderError::new(format!("Helper {}: {}", h.name(), e)))
+    }
+}
+
+pub fn register_exec_helper(handlebars: &mut Handlebars, config: &Config) {
+    handlebars.register_helper("exec", Box::new(ExecHelper::new(config)));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn render(helper: ExecHelper, template: &str) -> Result<String, RenderError> {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_helper("exec", Box::new(helper));
+        handlebars.render_template(template, &json!({"name": "notes"}))
+    }
+
+    fn helper(allow_list: &[&str]) -> ExecHelper {
+        ExecHelper {
+            enabled: true,
+            allow_list: allow_list.iter().map(|s| s.to_string()).collect(),
+            timeout: Duration::from_millis(2000),
+            dir: None,
+        }
+    }
+
+    #[test]
+    fn renders_trimmed_stdout() {
+        assert_eq!(
+            "hello notes",
+            render(helper(&["echo"]), "{{exec \"echo\" \"hello\" name}}").unwrap()
+        );
+        assert_eq!(
+            "a b",
+            render(helper(&["echo"]), "{{exec \"echo  a b \"}}").unwrap()
+        );
+    }
+
+    #[test]
+    fn refuses_commands_unless_allowed() {
+        let mut disabled = helper(&["echo"]);
+        disabled.enabled = false;
+        let e = render(disabled, "{{exec \"echo hi\"}}").unwrap_err();
+        assert!(e.to_string().contains("allow_exec = true"));
+
+        let e = render(helper(&["echo"]), "{{exec \"ls\"}}").unwrap_err();
+        assert!(e.to_string().contains("ls is not in exec_allow_list"));
+    }
+
+    #[test]
+    fn reports_failures_and_timeouts() {
+        let e = render(
+            helper(&["sh"]),
+            "{{exec \"sh\" \"-c\" \"echo oops >&2; exit 3\"}}",
+        )
+        .unwrap_err();
+        assert!(e.to_string().contains("failed with exit status: 3: oops"));
+
+        let mut slow = helper(&["sleep"]);
+        slow.timeout = Duration::from_millis(50);
+        let e = render(slow, "{{exec \"sleep 5\"}}").unwrap_err();
+        assert!(e.to_string().contains("`sleep 5` timed out after 50ms"));
+    }
+}
@@ -0,0 +1,137 @@
+use handlebars::{
+    template::{HelperTemplate, Parameter, TemplateElement},
+    Path, Template,
+};
+use regex::Regex;
+use std::{collections::BTreeSet, sync::OnceLock};
+
+use super::layouts::apply_layout;
+
+fn description_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?s)^\s*\{\{!(?:--(.*?)--|([^<].*?))\}\}").expect("valid regex"))
+}
+
+/// The first line of the comment a template starts with, ex. `{{!-- Daily log --}}`.
+/// A layout declaration on the first line is skipped.
+pub fn description(source: &str) -> Option<String> {
+    let source = match apply_layout(source) {
+        Some(_) => source.split_once('\n').map(|(_, rest)| rest).unwrap_or(""),
+        None => source,
+    };
+    let captures = description_regex().captures(source)?;
+    captures
+        .get(1)
+        .or(captures.get(2))?
+        .as_str()
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .map(str::to_string)
+}
+
+/// Paths a template reads from the data it is rendered with, ex. `name` or `config.meta.who`.
+pub fn referenced_variables(template: &Template) -> BTreeSet<String> {
+    let mut variables = BTreeSet::new();
+    collect_template(template, &mut variables);
+    variables
+}
+
+fn collect_template(template: &Template, variables: &mut BTreeSet<String>) {
+    for element in &template.elements {
+        collect_element(element, variables);
+    }
+}
+
+fn collect_element(element: &TemplateElement, variables: &mut BTreeSet<String>) {
+    match element {
+        TemplateElement::Expression(helper) | TemplateElement::HtmlExpression(helper) => {
+            if let (Parameter::Name(name), true, true) = (
+                &helper.name,
+                helper.params.is_empty(),
+                helper.hash.is_empty(),
+            ) {
+                variables.insert(name.clone());
+            }
+            collect_helper(helper, variables);
+        }
+        TemplateElement::HelperBlock(helper) => collect_helper(helper, variables),
+        TemplateElement::DecoratorExpression(decorator)
+        | TemplateElement::DecoratorBlock(decorator)
+        | TemplateElement::PartialExpression(decorator)
+        | TemplateElement::PartialBlock(decorator) => {
+            decorator
+                .params
+                .iter()
+                .chain(decorator.hash.values())
+                .for_each(|p| collect_parameter(p, variables));
+            if let Some(template) = &decorator.template {
+                collect_template(template, variables);
+            }
+        }
+        TemplateElement::RawString(_) | TemplateElement::Comment(_) => {}
+    }
+}
+
+fn collect_helper(helper: &HelperTemplate, variables: &mut BTreeSet<String>) {
+    collect_parameter(&helper.name, variables);
+    helper
+        .params
+        .iter()
+        .chain(helper.hash.values())
+        .for_each(|p| collect_parameter(p, variables));
+    for template in [&helper.template, &helper.inverse].into_iter().flatten() {
+        collect_template(template, variables);
+    }
+}
+
+fn collect_parameter(parameter: &Parameter, variables: &mut BTreeSet<String>) {
+    match parameter {
+        Parameter::Path(Path::Relative((_, raw))) if !matches!(raw.as_str(), "this" | ".") => {
+            variables.insert(raw.clone());
+        }
+        Parameter::Subexpression(subexpression) => {
+            collect_element(subexpression.as_element(), variables)
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reads_description_from_leading_comment() {
+        assert_eq!(
+            Some("Daily log".to_string()),
+            description("{{!-- Daily log --}}\n# {{name}}")
+        );
+        assert_eq!(
+            Some("Meeting notes".to_string()),
+            description("{{!< base}}\n{{! Meeting notes}}\nAgenda")
+        );
+        assert_eq!(
+            Some("First line".to_string()),
+            description("{{!--\n  First line\n  Second line\n--}}")
+        );
+        assert_eq!(None, description("# {{name}}\n{{!-- Not first --}}"));
+    }
+
+    #[test]
+    fn lists_referenced_variables() {
+        let template = Template::compile(
+            "# {{name}} {{date now fmt=\"[year]\"}} {{config.meta.who}}\n\
+             {{#if meta.author}}{{upper (default meta.author config.meta.who)}}{{/if}}\n\
+             {{#each tags}}{{this}} {{@index}}{{/each}}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec!["config.meta.who", "meta.author", "name", "now", "tags"],
+            referenced_variables(&template)
+                .into_iter()
+                .collect::<Vec<_>>()
+        );
+    }
+}
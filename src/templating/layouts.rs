@@ -0,0 +1,171 @@
+use handlebars::{
+    Context, Decorator, DecoratorDef, Handlebars, Helper, HelperDef, HelperResult, Output,
+    RenderContext, RenderError, Renderable,
+};
+use regex::Regex;
+use std::sync::OnceLock;
+
+pub static LAYOUTS_DIR: &str = "layouts/";
+/// Blocks are kept under their own prefix so `{{#*block "header"}}` does not replace the
+/// `header` partial.
+static BLOCK_PREFIX: &str = "@block/";
+/// The block a layout renders the template's content in.
+static BODY_BLOCK: &str = "body";
+static PARTIAL_BLOCK: &str = "@partial-block";
+
+fn layout_declaration_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^\{\{!<\s*([\w./-]+)\s*\}\}").expect("valid regex"))
+}
+
+/// Rewrites a template starting with `{{!< base}}` into a partial block around
+/// `layouts/base`. Returns the layout name and the rewritten template.
+///
+/// The layout declares blocks with defaults like `{{#block "title"}}Untitled{{/block}}`,
+/// which the template overrides with `{{#*block "title"}}My title{{/block}}`. The `body`
+/// block, or `{{> @partial-block}}`, renders the rest of the template.
+pub fn apply_layout(source: &str) -> Option<(String, String)> {
+    let captures = layout_declaration_regex().captures(source)?;
+    let layout = captures[1].to_string();
+    let rest = &source[captures[0].len()..];
+
+    // `~` trims the newline after the declaration so line numbers stay the same
+    let wrapped = format!(
+        "{{{{#> {dir}{layout}~}}}}{rest}{{{{/{dir}{layout}}}}}",
+        dir = LAYOUTS_DIR,
+    );
+
+    Some((layout, wrapped))
+}
+
+fn block_name<'a>(
+    name: &str,
+    param: Option<&'a serde_json::Value>,
+) -> Result<&'a str, RenderError> {
+    param.and_then(|p| p.as_str()).ok_or_else(|| {
+        RenderError::new(format!(
+            "{} needs the name of the block. Ex. {{{{#block \"title\"}}}}",
+            name
+        ))
+    })
+}
+
+/// `{{#block "title"}}Untitled{{/block}}` in a layout renders the template's `title` block, or
+/// its own content when the template has none. `body` defaults to the template's content.
+pub struct BlockHelper;
+
+impl HelperDef for BlockHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        r: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let name = block_name("Helper block", h.param(0).map(|p| p.value()))?;
+
+        // Rendered with a copy of `rc`, like partials, since the block is borrowed from it
+        let mut local_rc = rc.clone();
+        let template = rc
+            .get_partial(&format!("{}{}", BLOCK_PREFIX, name))
+            .or_else(|| match name == BODY_BLOCK {
+                true => rc.get_partial(PARTIAL_BLOCK),
+                false => None,
+            })
+            .or(h.template());
+        match template {
+            Some(template) => template.render(r, ctx, &mut local_rc, out),
+            None => Ok(()),
+        }
+    }
+}
+
+/// `{{#*block "title"}}My title{{/block}}` in a template fills the layout's `title` block.
+pub struct BlockDecorator;
+
+impl DecoratorDef for BlockDecorator {
+    fn call<'reg: 'rc, 'rc>(
+        &'reg self,
+        d: &Decorator<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        rc: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<(), RenderError> {
+        let name = block_name("Decorator block", d.param(0).map(|p| p.value()))?;
+        let template = d.template().ok_or_else(|| {
+            RenderError::new(format!(
+                "Decorator block: use {{{{#*block \"{}\"}}}}...{{{{/block}}}}",
+                name
+            ))
+        })?;
+
+        rc.set_partial(format!("{}{}", BLOCK_PREFIX, name), template);
+        Ok(())
+    }
+}
+
+pub fn register_layout_helpers(handlebars: &mut Handlebars) {
+    handlebars.register_helper("block", Box::new(BlockHelper));
+    handlebars.register_decorator("block", Box::new(BlockDecorator));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use handlebars::Handlebars;
+    use serde_json::json;
+
+    #[test]
+    fn leaves_templates_without_layout_alone() {
+        assert!(apply_layout("# {{name}}\n").is_none());
+        assert!(apply_layout("# {{name}}\n{{!< base}}").is_none());
+    }
+
+    fn render(layout: &str, template: &str) -> String {
+        let (_, source) = apply_layout(template).unwrap();
+
+        let mut handlebars = Handlebars::new();
+        register_layout_helpers(&mut handlebars);
+        handlebars.register_partial("header", "Header").unwrap();
+        handlebars.register_partial("layouts/base", layout).unwrap();
+        handlebars.register_template_string("test", source).unwrap();
+        handlebars.render("test", &json!({"name": "x"})).unwrap()
+    }
+
+    #[test]
+    fn fills_layout_blocks() {
+        let (layout, _) = apply_layout("{{!< base}}\n").unwrap();
+        assert_eq!("base", layout);
+
+        assert_eq!(
+            "# Meeting x\n\nAgenda\n---\nFooter\n",
+            render(
+                "# {{#block \"title\"}}{{name}}{{/block}}\n{{block \"body\"}}---\n{{#block \"footer\"}}Footer{{/block}}\n",
+                "{{!< base}}\n{{#*block \"title\"}}Meeting {{name}}{{/block}}\nAgenda\n",
+            )
+        );
+    }
+
+    #[test]
+    fn renders_the_body_as_partial_block_too() {
+        assert_eq!(
+            "Agenda\n|Agenda\n",
+            render(
+                "{{> @partial-block}}|{{#block \"body\"}}Empty{{/block}}",
+                "{{!< base}}\nAgenda\n",
+            )
+        );
+    }
+
+    #[test]
+    fn keeps_blocks_apart_from_partials() {
+        assert_eq!(
+            "[My header] Header\n",
+            render(
+                "[{{#block \"header\"}}{{/block}}] {{block \"body\"}}",
+                "{{!< base}}\n{{#*block \"header\"}}My header{{/block}}{{> header}}\n",
+            )
+        );
+    }
+}
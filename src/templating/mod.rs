@@ -0,0 +1,350 @@
+use anyhow::{anyhow, Context};
+use handlebars::{Handlebars, Helper, HelperDef, RenderContext, RenderError, ScopedJson};
+use serde_json::Value;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+};
+use walkdir::WalkDir;
+
+use crate::config::Config;
+use crate::notes::Notes;
+
+mod date;
+mod embed;
+mod embedded;
+mod exec;
+pub mod inspect;
+mod layouts;
+mod periodic;
+mod query;
+mod scripts;
+mod strings;
+
+use embedded::EMBEDDED_TEMPLATES;
+pub use layouts::{apply_layout, LAYOUTS_DIR};
+
+pub use date::{add_months, parse_date, parse_weekday, Now};
+
+pub static EMPTY_TEMPLATE_NAME: &str = "empty";
+pub static TEMPLATE_EXTENSION: &str = "hbs";
+static PARTIALS_DIR: &str = "partials/";
+static VAULT_TEMPLATES_DIR: &str = ".templates";
+
+type HelperFn = fn(&Helper) -> Result<Value, RenderError>;
+
+/// Adapts a plain function to a helper usable both as `{{helper}}` and as a `(helper)` subexpression.
+struct FnHelper(HelperFn);
+
+impl HelperDef for FnHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc handlebars::Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg, 'rc>, RenderError> {
+        Ok(ScopedJson::Derived((self.0)(h)?))
+    }
+}
+
+/// Notes in `notes_dir`, loaded the first time a helper needs them.
+pub struct LazyNotes {
+    config: Config,
+    notes: OnceLock<Notes>,
+}
+
+impl LazyNotes {
+    pub fn new(config: &Config) -> LazyNotes {
+        LazyNotes {
+            config: config.clone(),
+            notes: OnceLock::new(),
+        }
+    }
+
+    pub fn get(&self) -> Result<&Notes, RenderError> {
+        if let Some(notes) = self.notes.get() {
+            return Ok(notes);
+        }
+        let notes = Notes::load(&self.config)
+            .map_err(|e| RenderError::new(format!("Could not load notes: {:#}", e)))?;
+        Ok(self.notes.get_or_init(|| notes))
+    }
+}
+
+#[tracing::instrument]
+pub fn get_templates(config: &Config) -> anyhow::Result<Handlebars<'_>> {
+    let mut handlebars = Handlebars::new();
+    handlebars.set_strict_mode(config.strict_templates);
+
+    date::register_date_helpers(&mut handlebars);
+    strings::register_string_helpers(&mut handlebars);
+    layouts::register_layout_helpers(&mut handlebars);
+    exec::register_exec_helper(&mut handlebars, config);
+    let notes = Arc::new(LazyNotes::new(config));
+    embed::register_embed_helper(&mut handlebars, notes.clone());
+    query::register_query_helper(&mut handlebars, notes.clone());
+    periodic::register_periodic_helpers(&mut handlebars, notes);
+    // Registered from the lowest layer up so scripts in earlier layers replace later ones
+    for (_, dir) in template_layers(config).iter().rev() {
+        scripts::register_script_helpers(&mut handlebars, dir)?;
+    }
+
+    register_templates(&mut handlebars, &template_files(config)?)?;
+
+    Ok(handlebars)
+}
+
+/// Where a template comes from. Templates in earlier layers override templates with the same
+/// name in later layers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    /// `.templates` in `notes_dir`
+    Vault,
+    /// `templates_dir`
+    User,
+    /// `team_templates_dir`
+    Team,
+    /// Built into the binary
+    Embedded,
+}
+
+impl Layer {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Layer::Vault => "vault",
+            Layer::User => "user",
+            Layer::Team => "team",
+            Layer::Embedded => "embedded",
+        }
+    }
+}
+
+/// The template directories of `config` in the order they are searched.
+pub fn template_layers(config: &Config) -> Vec<(Layer, PathBuf)> {
+    [
+        Some((Layer::Vault, config.notes_dir.join(VAULT_TEMPLATES_DIR))),
+        Some((Layer::User, config.templates_dir.clone())),
+        config
+            .team_templates_dir
+            .clone()
+            .map(|dir| (Layer::Team, dir)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateKind {
+    Template,
+    Partial,
+    Layout,
+}
+
+impl TemplateKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TemplateKind::Template => "template",
+            TemplateKind::Partial => "partial",
+            TemplateKind::Layout => "layout",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TemplateSource {
+    File(PathBuf),
+    Embedded(&'static str),
+}
+
+#[derive(Debug, Clone)]
+pub struct TemplateFile {
+    /// Name the file is registered as, ex. `work/meeting`, `header` or `layouts/base`
+    pub name: String,
+    pub kind: TemplateKind,
+    pub layer: Layer,
+    pub source: TemplateSource,
+}
+
+impl TemplateFile {
+    fn new(relative_path: &Path, layer: Layer, source: TemplateSource) -> TemplateFile {
+        let name = relative_path
+            .with_extension("")
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let (name, kind) = if let Some(partial) = name.strip_prefix(PARTIALS_DIR) {
+            (partial.to_string(), TemplateKind::Partial)
+        } else if name.starts_with(LAYOUTS_DIR) {
+            (name, TemplateKind::Layout)
+        } else {
+            (name, TemplateKind::Template)
+        };
+
+        TemplateFile {
+            name,
+            kind,
+            layer,
+            source,
+        }
+    }
+
+    pub fn read(&self) -> anyhow::Result<String> {
+        match &self.source {
+            TemplateSource::File(path) => {
+                fs::read_to_string(path).with_context(|| format!("Could not read {:?}", path))
+            }
+            TemplateSource::Embedded(source) => Ok(source.to_string()),
+        }
+    }
+
+    /// Where the template is read from, ex. `"/home/max/templates/daily.hbs"` or `embedded`.
+    pub fn location(&self) -> String {
+        match &self.source {
+            TemplateSource::File(path) => format!("{:?}", path),
+            TemplateSource::Embedded(_) => Layer::Embedded.as_str().to_string(),
+        }
+    }
+}
+
+/// The templates of every layer, without those overridden by a template with the same name in
+/// an earlier layer. Sorted by name.
+pub fn template_files(config: &Config) -> anyhow::Result<Vec<TemplateFile>> {
+    let mut files: Vec<TemplateFile> = vec![];
+    for (layer, dir) in template_layers(config) {
+        files.extend(
+            directory_files(&dir, layer)
+                .with_context(|| format!("Could not read templates directory {:?}", dir))?,
+        );
+    }
+    files.extend(embedded_template_files());
+
+    let mut effective: Vec<TemplateFile> = vec![];
+    for file in files {
+        if !effective.iter().any(|f| f.name == file.name) {
+            effective.push(file);
+        }
+    }
+    effective.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(effective)
+}
+
+/// The templates built into the binary.
+pub fn embedded_template_files() -> Vec<TemplateFile> {
+    EMBEDDED_TEMPLATES
+        .iter()
+        .map(|(path, source)| {
+            TemplateFile::new(
+                Path::new(path),
+                Layer::Embedded,
+                TemplateSource::Embedded(source),
+            )
+        })
+        .collect()
+}
+
+/// Every `.hbs` file in `dir`, named by its path without extension, ex. `work/meeting`.
+/// Files in `partials/` are partials named without the folder, ex. `{{> header}}`, and
+/// files in `layouts/` are layouts templates can declare with `{{!< base}}`.
+fn directory_files(dir: &Path, layer: Layer) -> anyhow::Result<Vec<TemplateFile>> {
+    if !dir.exists() {
+        tracing::debug!("Template directory {:?} does not exist", dir);
+        return Ok(vec![]);
+    }
+
+    let mut files = vec![];
+    for entry in WalkDir::new(dir)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| {
+            let name = e.file_name().to_string_lossy();
+            !(name.starts_with('.') || name.starts_with('#'))
+        })
+    {
+        let path = entry?.into_path();
+        if path.extension() != Some(TEMPLATE_EXTENSION.as_ref()) {
+            continue;
+        }
+
+        files.push(TemplateFile::new(
+            path.strip_prefix(dir)?,
+            layer,
+            TemplateSource::File(path.clone()),
+        ));
+    }
+
+    Ok(files)
+}
+
+fn register_templates(handlebars: &mut Handlebars, files: &[TemplateFile]) -> anyhow::Result<()> {
+    let mut layouts = vec![];
+    for file in files {
+        let source = file.read()?;
+
+        match file.kind {
+            TemplateKind::Partial => handlebars
+                .register_partial(&file.name, source)
+                .with_context(|| format!("Could not register partial {}", file.location()))?,
+            TemplateKind::Layout => handlebars
+                .register_partial(&file.name, source)
+                .with_context(|| format!("Could not register layout {}", file.location()))?,
+            TemplateKind::Template => {
+                let source = match apply_layout(&source) {
+                    Some((layout, wrapped)) => {
+                        layouts.push((file, layout));
+                        wrapped
+                    }
+                    None => source,
+                };
+                handlebars
+                    .register_template_string(&file.name, source)
+                    .with_context(|| format!("Could not register template {}", file.location()))?
+            }
+        }
+    }
+
+    for (file, layout) in layouts {
+        if handlebars
+            .get_template(&format!("{}{}", LAYOUTS_DIR, layout))
+            .is_none()
+        {
+            return Err(anyhow!(
+                "Template {} uses layout {} but {}{}.{} does not exist in any templates directory",
+                file.location(),
+                layout,
+                LAYOUTS_DIR,
+                layout,
+                TEMPLATE_EXTENSION
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod test_utils {
+    use super::*;
+
+    /// A `notes_dir` in a temp dir with the given `(name, contents)` notes, and a config for it.
+    pub fn notes_dir(notes: &[(&str, &str)]) -> (PathBuf, Config) {
+        let dir = std::env::temp_dir().join(format!("notes-cli-notes-{}", uuid::Uuid::new_v4()));
+        for (name, contents) in notes {
+            let path = dir.join(format!("{}.md", name));
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        let config = toml::from_str(&format!(
+            "notes_dir = {:?}\ntemplates_dir = {:?}\n[subcommands]\n[meta]\n",
+            dir, dir
+        ))
+        .unwrap();
+        (dir, config)
+    }
+}
@@ -0,0 +1,283 @@
+use handlebars::{Context, Handlebars, Helper, HelperDef, RenderContext, RenderError, ScopedJson};
+use serde_json::Value;
+use std::sync::Arc;
+use time::{
+    format_description::BorrowedFormatItem, parsing::Parsed, Date, PrimitiveDateTime, Weekday,
+};
+
+use super::{
+    date::{date_param, fmt_param},
+    LazyNotes,
+};
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Previous,
+    Next,
+}
+
+/// Finds the nearest existing note before or after a date in a series of periodic notes, ex.
+/// `{{previous_note now fmt="journal/[year]-[month]-[day]"}}`. The series is the notes whose
+/// name matches `fmt`, the same format the series' name template passes to `date`, followed by
+/// `suffix` when given, ex. `suffix="-standup"` for `{{date now fmt="[year]-[month]-[day]"}}-standup`.
+///
+/// Renders the note's name, or nothing when there is no such note, so it can be used with
+/// `{{#with (previous_note now)}}[[{{this}}]]{{/with}}`.
+pub struct PeriodicHelper {
+    notes: Arc<LazyNotes>,
+    direction: Direction,
+}
+
+/// The parts of a note name in the series, when all of `name` matches `description`.
+fn parse_name(name: &str, description: &[BorrowedFormatItem]) -> Option<Parsed> {
+    let mut parsed = Parsed::new();
+    let remaining = parsed.parse_items(name.as_bytes(), description).ok()?;
+    remaining.is_empty().then_some(parsed)
+}
+
+/// An ISO week only names a date together with its ISO year. `[year]` is the calendar year,
+/// which differs around new year, ex. 2024-12-30 is in week 1 of 2025.
+fn has_iso_week_without_iso_year(parsed: &Parsed) -> bool {
+    parsed.iso_week_number().is_some() && parsed.iso_year().is_none()
+}
+
+/// The date a note name in the series stands for. Weekly and monthly names stand for their
+/// first day.
+fn series_date(mut parsed: Parsed) -> Option<Date> {
+    if has_iso_week_without_iso_year(&parsed) {
+        return None;
+    }
+    if parsed.weekday().is_none()
+        && (parsed.iso_week_number().is_some()
+            || parsed.monday_week_number().is_some()
+            || parsed.sunday_week_number().is_some())
+    {
+        parsed.set_weekday(Weekday::Monday)?;
+    }
+    if parsed.day().is_none() && parsed.month().is_some() {
+        parsed.set_day(1.try_into().ok()?)?;
+    }
+    if parsed.month().is_none() && parsed.ordinal().is_none() && parsed.weekday().is_none() {
+        parsed.set_month(time::Month::January)?;
+        parsed.set_day(1.try_into().ok()?)?;
+    }
+
+    Date::try_from(parsed).ok()
+}
+
+impl HelperDef for PeriodicHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg, 'rc>, RenderError> {
+        let (fmt, description) = fmt_param(h)?;
+        let date: PrimitiveDateTime = date_param(h)?;
+        let suffix = match h.hash_get("suffix").map(|s| s.value()) {
+            None => "",
+            Some(Value::String(suffix)) => suffix.as_str(),
+            Some(other) => {
+                return Err(RenderError::new(format!(
+                    "Helper {}: suffix must be a string, got {}",
+                    h.name(),
+                    other
+                )))
+            }
+        };
+
+        let parsed = date
+            .format(&description)
+            .ok()
+            .and_then(|name| parse_name(&name, &description));
+        if parsed.as_ref().is_some_and(has_iso_week_without_iso_year) {
+            return Err(RenderError::new(format!(
+                "Helper {}: fmt \"{}\" has [week_number] without [year base:iso_week]",
+                h.name(),
+                fmt
+            )));
+        }
+        let current = parsed.and_then(series_date).ok_or_else(|| {
+            RenderError::new(format!(
+                "Helper {}: fmt \"{}\" does not identify a day, week, month or year",
+                h.name(),
+                fmt
+            ))
+        })?;
+
+        let series = self.notes.get()?.iter().filter_map(|note| {
+            let parsed = parse_name(note.name.strip_suffix(suffix)?, &description)?;
+            Some((series_date(parsed)?, &note.name))
+        });
+        let nearest = match self.direction {
+            Direction::Previous => series.filter(|(d, _)| *d < current).max(),
+            Direction::Next => series.filter(|(d, _)| *d > current).min(),
+        };
+
+        Ok(ScopedJson::Derived(
+            nearest.map_or(Value::Null, |(_, name)| Value::String(name.clone())),
+        ))
+    }
+}
+
+pub fn register_periodic_helpers(handlebars: &mut Handlebars, notes: Arc<LazyNotes>) {
+    for (name, direction) in [
+        ("previous_note", Direction::Previous),
+        ("next_note", Direction::Next),
+    ] {
+        handlebars.register_helper(
+            name,
+            Box::new(PeriodicHelper {
+                notes: notes.clone(),
+                direction,
+            }),
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::templating::test_utils::notes_dir;
+    use serde_json::json;
+    use std::fs;
+
+    fn render(config: &crate::config::Config, template: &str) -> Result<String, RenderError> {
+        render_at(config, template, "2024-03-06T09:00:00")
+    }
+
+    fn render_at(
+        config: &crate::config::Config,
+        template: &str,
+        now: &str,
+    ) -> Result<String, RenderError> {
+        let mut handlebars = Handlebars::new();
+        register_periodic_helpers(&mut handlebars, Arc::new(LazyNotes::new(config)));
+        handlebars.render_template(template, &json!({ "now": now }))
+    }
+
+    #[test]
+    fn finds_nearest_daily_notes_skipping_gaps() {
+        let (dir, config) = notes_dir(&[
+            ("journal/2024-03-01", ""),
+            ("journal/2024-03-04", ""),
+            ("journal/2024-03-06", ""),
+            ("journal/2024-03-11", ""),
+            ("journal/2024-03-20", ""),
+            ("journal/notes", ""),
+            ("2024-03-05", ""),
+        ]);
+        let fmt = "fmt=\"journal/[year]-[month]-[day]\"";
+
+        assert_eq!(
+            "journal/2024-03-04 journal/2024-03-11",
+            render(
+                &config,
+                &format!("{{{{previous_note now {fmt}}}}} {{{{next_note now {fmt}}}}}")
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            "[[2024-03-05]]",
+            render(
+                &config,
+                "{{#with (previous_note now)}}[[{{this}}]]{{/with}}{{#with (next_note now)}}[[{{this}}]]{{/with}}"
+            )
+            .unwrap()
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn finds_weekly_and_monthly_notes() {
+        let (dir, config) = notes_dir(&[
+            ("weekly/2024-W08", ""),
+            ("weekly/2024-W10", ""),
+            ("weekly/2024-W12", ""),
+            ("monthly/2023-12", ""),
+            ("monthly/2024-03", ""),
+        ]);
+
+        assert_eq!(
+            "weekly/2024-W08 weekly/2024-W12",
+            render(
+                &config,
+                "{{previous_note now fmt=\"weekly/[year base:iso_week]-W[week_number]\"}} {{next_note now fmt=\"weekly/[year base:iso_week]-W[week_number]\"}}"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            "monthly/2023-12",
+            render(
+                &config,
+                "{{previous_note now fmt=\"monthly/[year]-[month]\"}}{{next_note now fmt=\"monthly/[year]-[month]\"}}"
+            )
+            .unwrap()
+        );
+        assert!(render(&config, "{{previous_note now fmt=\"[hour]\"}}")
+            .unwrap_err()
+            .to_string()
+            .contains("does not identify a day, week, month or year"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn finds_weekly_notes_around_new_year() {
+        let (dir, config) = notes_dir(&[
+            ("weekly/2020-W52", ""),
+            ("weekly/2020-W53", ""),
+            ("weekly/2021-W01", ""),
+            ("weekly/2024-W51", ""),
+            ("weekly/2025-W02", ""),
+        ]);
+        let fmt = "fmt=\"weekly/[year base:iso_week]-W[week_number]\"";
+        let template = format!("{{{{previous_note now {fmt}}}}} {{{{next_note now {fmt}}}}}");
+
+        assert_eq!(
+            "weekly/2024-W51 weekly/2025-W02",
+            render_at(&config, &template, "2024-12-30T09:00:00").unwrap()
+        );
+        assert_eq!(
+            "weekly/2020-W52 weekly/2021-W01",
+            render_at(&config, &template, "2021-01-01T09:00:00").unwrap()
+        );
+        assert!(render_at(
+            &config,
+            "{{previous_note now fmt=\"weekly/[year]-W[week_number]\"}}",
+            "2021-01-01T09:00:00"
+        )
+        .unwrap_err()
+        .to_string()
+        .contains("has [week_number] without [year base:iso_week]"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn finds_notes_with_a_suffix_after_the_date() {
+        let (dir, config) = notes_dir(&[
+            ("2024-03-01-standup", ""),
+            ("2024-03-04-retro", ""),
+            ("2024-03-05", ""),
+            ("2024-03-08-standup", ""),
+        ]);
+
+        assert_eq!(
+            "2024-03-01-standup 2024-03-08-standup",
+            render(
+                &config,
+                "{{previous_note now fmt=\"[year]-[month]-[day]\" suffix=\"-standup\"}} {{next_note now fmt=\"[year]-[month]-[day]\" suffix=\"-standup\"}}"
+            )
+            .unwrap()
+        );
+        assert!(render(&config, "{{previous_note now suffix=1}}")
+            .unwrap_err()
+            .to_string()
+            .contains("suffix must be a string"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
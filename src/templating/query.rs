@@ -0,0 +1,230 @@
+use handlebars::{Context, Handlebars, Helper, HelperDef, RenderContext, RenderError, ScopedJson};
+use regex::Regex;
+use serde_json::{json, Value};
+use std::{
+    cmp::Ordering,
+    fs,
+    sync::{Arc, OnceLock},
+};
+use time::{OffsetDateTime, PrimitiveDateTime};
+
+use super::{parse_date, LazyNotes, Now};
+use crate::config::parse_timezone;
+use crate::natural_date::parse_natural_date;
+use crate::notes::Note;
+
+fn duration_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^\d+ ?[a-z]+$").expect("valid regex"))
+}
+
+/// Lists notes in `notes_dir` for `{{#each}}`, ex.
+/// `{{#each (notes tag="meeting" since="30d" sort="-created" limit=10)}}`.
+///
+/// Each note has `name`, `path`, `title`, `tags`, `aliases`, `front_matter` and `created`,
+/// which is the `created` or `date` front matter, or else when the file was last modified.
+pub struct NotesHelper {
+    notes: Arc<LazyNotes>,
+}
+
+struct Entry<'a> {
+    note: &'a Note,
+    created: Option<PrimitiveDateTime>,
+    modified: Option<PrimitiveDateTime>,
+}
+
+fn modified(note: &Note) -> Option<PrimitiveDateTime> {
+    let modified = OffsetDateTime::from(fs::metadata(&note.path).ok()?.modified().ok()?);
+    Some(PrimitiveDateTime::new(modified.date(), modified.time()))
+}
+
+fn created(note: &Note) -> Option<PrimitiveDateTime> {
+    ["created", "date"]
+        .iter()
+        .find_map(|key| note.front_matter.get(key).and_then(parse_date))
+}
+
+/// `now` of the rendered template, so `since` follows `new --date`.
+fn template_now(ctx: &Context) -> OffsetDateTime {
+    ctx.data()
+        .get("now")
+        .and_then(|now| {
+            let offset = now
+                .get("offset")
+                .and_then(|o| o.as_str())
+                .and_then(|o| parse_timezone(o).ok())?;
+            parse_date(now).map(|dt| dt.assume_offset(offset))
+        })
+        .unwrap_or_else(OffsetDateTime::now_utc)
+}
+
+impl HelperDef for NotesHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg, 'rc>, RenderError> {
+        let error = |e: String| RenderError::new(format!("Helper {}: {}", h.name(), e));
+        let hash_str = |key: &str| h.hash_get(key).and_then(|v| v.value().as_str());
+
+        // Whole days, so `since="yesterday"` includes all of yesterday
+        let since = match hash_str("since") {
+            Some(since) => {
+                // `30d` means the last 30 days rather than 30 days from now
+                let since = match duration_regex().is_match(since.trim()) {
+                    true => format!("-{}", since.trim()),
+                    false => since.to_string(),
+                };
+                let since = parse_natural_date(&since, template_now(ctx))
+                    .map_err(|e| error(e.to_string()))?;
+                Some(since.date())
+            }
+            None => None,
+        };
+        let tag = hash_str("tag").map(|t| t.trim_start_matches('#'));
+        let limit = match h.hash_get("limit").map(|v| v.value()) {
+            Some(limit) => Some(
+                limit
+                    .as_u64()
+                    .ok_or_else(|| error(format!("limit must be a number, got {}", limit)))?
+                    as usize,
+            ),
+            None => None,
+        };
+        let sort = hash_str("sort").unwrap_or("name");
+        let (descending, key) = match sort.strip_prefix('-') {
+            Some(key) => (true, key),
+            None => (false, sort),
+        };
+
+        let mut entries: Vec<Entry> = self
+            .notes
+            .get()?
+            .iter()
+            .filter(|note| tag.is_none_or(|tag| note.tags.iter().any(|t| t == tag)))
+            .map(|note| {
+                let modified = modified(note);
+                Entry {
+                    note,
+                    created: created(note).or(modified),
+                    modified,
+                }
+            })
+            .filter(|entry| {
+                since.is_none_or(|since| entry.created.is_some_and(|c| c.date() >= since))
+            })
+            .collect();
+
+        let compare: fn(&Entry, &Entry) -> Ordering = match key {
+            "name" => |a, b| a.note.name.cmp(&b.note.name),
+            "title" => |a, b| {
+                a.note
+                    .title
+                    .to_lowercase()
+                    .cmp(&b.note.title.to_lowercase())
+            },
+            "created" => |a, b| a.created.cmp(&b.created),
+            "modified" => |a, b| a.modified.cmp(&b.modified),
+            _ => {
+                return Err(error(format!(
+                "can not sort by {}. Use name, title, created or modified, with - for descending",
+                key
+            )))
+            }
+        };
+        entries.sort_by(|a, b| match descending {
+            true => compare(b, a),
+            false => compare(a, b),
+        });
+        entries.truncate(limit.unwrap_or(usize::MAX));
+
+        let notes = entries
+            .into_iter()
+            .map(|entry| {
+                let mut value = serde_json::to_value(entry.note)?;
+                value["created"] =
+                    json!(entry.created.map(|created| Now::from(created.assume_utc())));
+                Ok(value)
+            })
+            .collect::<Result<Vec<Value>, serde_json::Error>>()
+            .map_err(|e| error(e.to_string()))?;
+
+        Ok(ScopedJson::Derived(Value::Array(notes)))
+    }
+}
+
+pub fn register_query_helper(handlebars: &mut Handlebars, notes: Arc<LazyNotes>) {
+    handlebars.register_helper("notes", Box::new(NotesHelper { notes }));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::templating::{
+        date::register_date_helpers, strings::register_string_helpers, test_utils::notes_dir,
+    };
+    use time::macros::datetime;
+
+    fn render(config: &crate::config::Config, template: &str) -> Result<String, RenderError> {
+        let mut handlebars = Handlebars::new();
+        register_date_helpers(&mut handlebars);
+        register_string_helpers(&mut handlebars);
+        register_query_helper(&mut handlebars, Arc::new(LazyNotes::new(config)));
+        handlebars.render_template(
+            template,
+            &json!({ "now": Now::from(datetime!(2024-03-20 12:00 +01:00)) }),
+        )
+    }
+
+    #[test]
+    fn lists_notes_by_tag_date_and_sort() {
+        let (dir, config) = notes_dir(&[
+            (
+                "meetings/kickoff",
+                "---\ntitle: Kickoff\ncreated: 2024-03-01\ntags: [meeting]\n---\n",
+            ),
+            (
+                "meetings/retro",
+                "---\ntitle: Retro\ndate: 2024-03-15 10:00\n---\n#meeting\n",
+            ),
+            (
+                "meetings/old",
+                "---\ntitle: Old\ncreated: 2023-12-01\ntags: [meeting]\n---\n",
+            ),
+            ("ideas", "---\ncreated: 2024-03-10\n---\n# Ideas\n"),
+        ]);
+
+        assert_eq!(
+            "Retro 2024-03-15 [meeting],Kickoff 2024-03-01 [meeting],",
+            render(
+                &config,
+                "{{#each (notes tag=\"meeting\" since=\"30d\" sort=\"-created\")}}{{title}} {{date created}} [{{join tags \",\"}}],{{/each}}"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            "ideas meetings/kickoff ",
+            render(
+                &config,
+                "{{#each (notes since=\"2024-03-01\" limit=2)}}{{name}} {{/each}}"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            "Ideas,Kickoff,Old,Retro,",
+            render(
+                &config,
+                "{{#each (notes sort=\"title\")}}{{title}},{{/each}}"
+            )
+            .unwrap()
+        );
+        assert!(render(&config, "{{#each (notes sort=\"size\")}}{{/each}}")
+            .unwrap_err()
+            .to_string()
+            .contains("can not sort by size"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
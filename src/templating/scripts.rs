@@ -0,0 +1,166 @@
+use anyhow::Context as _;
+use handlebars::{Context, Handlebars, Helper, HelperDef, RenderContext, RenderError, ScopedJson};
+use rhai::{
+    serde::{from_dynamic, to_dynamic},
+    Dynamic, Engine, Scope, AST,
+};
+use serde_json::{Map, Value};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+pub static HELPERS_DIR: &str = "helpers";
+static SCRIPT_EXTENSION: &str = "rhai";
+static MAX_OPERATIONS: u64 = 1_000_000;
+
+/// A helper implemented as a rhai script. The script gets the helper's arguments as
+/// `params` and its hash arguments as `hash`, and its last expression is the result.
+pub struct ScriptHelper {
+    path: PathBuf,
+    engine: Arc<Engine>,
+    ast: AST,
+}
+
+impl HelperDef for ScriptHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg, 'rc>, RenderError> {
+        let error = |e: &dyn std::fmt::Display| {
+            RenderError::new(format!(
+                "Helper {}: script {:?} failed: {}",
+                h.name(),
+                self.path,
+                e
+            ))
+        };
+
+        let params: Vec<Value> = h.params().iter().map(|p| p.value().clone()).collect();
+        let hash: Map<String, Value> = h
+            .hash()
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.value().clone()))
+            .collect();
+
+        let mut scope = Scope::new();
+        scope.push_dynamic("params", to_dynamic(params).map_err(|e| error(&e))?);
+        scope.push_dynamic("hash", to_dynamic(hash).map_err(|e| error(&e))?);
+
+        let result = self
+            .engine
+            .eval_ast_with_scope::<Dynamic>(&mut scope, &self.ast)
+            .map_err(|e| error(&e))?;
+
+        Ok(ScopedJson::Derived(
+            from_dynamic::<Value>(&result).map_err(|e| error(&e))?,
+        ))
+    }
+}
+
+/// Registers every `helpers/*.rhai` file in `templates_dir` as a helper named after the file.
+pub fn register_script_helpers(
+    handlebars: &mut Handlebars,
+    templates_dir: &Path,
+) -> anyhow::Result<()> {
+    let dir = templates_dir.join(HELPERS_DIR);
+    if !dir.is_dir() {
+        return Ok(());
+    }
+
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    let engine = Arc::new(engine);
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+        .with_context(|| format!("Could not read helpers directory {:?}", dir))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == SCRIPT_EXTENSION))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let Some(name) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let source = fs::read_to_string(&path)
+            .with_context(|| format!("Could not read helper script {:?}", path))?;
+        let ast = engine
+            .compile(&source)
+            .map_err(|e| anyhow::anyhow!("Could not compile helper script {:?}: {}", path, e))?;
+
+        handlebars.register_helper(
+            &name,
+            Box::new(ScriptHelper {
+                path,
+                engine: engine.clone(),
+                ast,
+            }),
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn templates_dir(scripts: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("notes-cli-scripts-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join(HELPERS_DIR)).unwrap();
+        for (name, source) in scripts {
+            fs::write(dir.join(HELPERS_DIR).join(name), source).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn registers_scripts_by_file_name() {
+        let dir = templates_dir(&[
+            (
+                "sprint.rhai",
+                "let week = params[0]; `S${(week - 1) / 2 + 1}`",
+            ),
+            ("greet.rhai", "`${hash.greeting} ${params[0]}`"),
+        ]);
+        let mut handlebars = Handlebars::new();
+        register_script_helpers(&mut handlebars, &dir).unwrap();
+
+        assert_eq!(
+            "S3 Hi Max",
+            handlebars
+                .render_template(
+                    "{{sprint week}} {{greet \"Max\" greeting=\"Hi\"}}",
+                    &json!({"week": 6})
+                )
+                .unwrap()
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reports_script_and_line_of_errors() {
+        let dir = templates_dir(&[("broken.rhai", "let a = 1;\nlet b = ;")]);
+        let e = register_script_helpers(&mut Handlebars::new(), &dir).unwrap_err();
+        assert!(e.to_string().contains("broken.rhai"));
+        assert!(e.to_string().contains("line 2"));
+        fs::remove_dir_all(dir).unwrap();
+
+        let dir = templates_dir(&[("fails.rhai", "let a = 1;\nthrow \"no sprint\";")]);
+        let mut handlebars = Handlebars::new();
+        register_script_helpers(&mut handlebars, &dir).unwrap();
+        let e = handlebars
+            .render_template("{{fails}}", &json!({}))
+            .unwrap_err();
+        assert!(e.to_string().contains("fails.rhai"));
+        assert!(e.to_string().contains("no sprint"));
+        assert!(e.to_string().contains("line 2"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
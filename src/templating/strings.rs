@@ -0,0 +1,328 @@
+use handlebars::{Handlebars, Helper, JsonRender, RenderError};
+use regex::Regex;
+use serde_json::Value;
+
+use super::{FnHelper, HelperFn};
+
+pub fn register_string_helpers(handlebars: &mut Handlebars) {
+    let helpers: [(&str, HelperFn); 12] = [
+        ("slugify", slugify),
+        ("lower", lower),
+        ("upper", upper),
+        ("title_case", title_case),
+        ("truncate", truncate),
+        ("replace", replace),
+        ("regex_replace", regex_replace),
+        ("default", default),
+        ("join", join),
+        ("split", split),
+        ("trim", trim),
+        ("pad", pad),
+    ];
+    for (name, helper) in helpers {
+        handlebars.register_helper(name, Box::new(FnHelper(helper)));
+    }
+}
+
+fn to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        other => other.render(),
+    }
+}
+
+fn string_param(h: &Helper, index: usize, what: &str) -> Result<String, RenderError> {
+    h.param(index)
+        .map(|p| to_string(p.value()))
+        .ok_or_else(|| RenderError::new(format!("Helper {}: missing {}", h.name(), what)))
+}
+
+fn usize_param(h: &Helper, index: usize, what: &str) -> Result<usize, RenderError> {
+    h.param(index)
+        .and_then(|p| match p.value() {
+            Value::Number(n) => n.as_u64().map(|n| n as usize),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            RenderError::new(format!(
+                "Helper {}: {} must be a positive whole number",
+                h.name(),
+                what
+            ))
+        })
+}
+
+fn hash_str(h: &Helper, key: &str) -> Option<String> {
+    h.hash_get(key).map(|v| to_string(v.value()))
+}
+
+/// `{{slugify name}}`. Ex. "Weekly Sync: Q1" -> "weekly-sync-q1"
+fn slugify(h: &Helper) -> Result<Value, RenderError> {
+    let text = string_param(h, 0, "text")?;
+    let slug = text
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    Ok(Value::String(slug))
+}
+
+/// `{{lower name}}`
+fn lower(h: &Helper) -> Result<Value, RenderError> {
+    Ok(Value::String(string_param(h, 0, "text")?.to_lowercase()))
+}
+
+/// `{{upper name}}`
+fn upper(h: &Helper) -> Result<Value, RenderError> {
+    Ok(Value::String(string_param(h, 0, "text")?.to_uppercase()))
+}
+
+/// `{{title_case name}}`. Ex. "weekly sync" -> "Weekly Sync"
+fn title_case(h: &Helper) -> Result<Value, RenderError> {
+    let text = string_param(h, 0, "text")?;
+    let mut capitalize = true;
+    let title = text
+        .chars()
+        .map(|c| {
+            let out: String = if capitalize {
+                c.to_uppercase().collect()
+            } else {
+                c.to_string()
+            };
+            capitalize = c.is_whitespace() || c == '-' || c == '_';
+            out
+        })
+        .collect();
+
+    Ok(Value::String(title))
+}
+
+/// `{{truncate name 20 suffix="..."}}`. The suffix is only added when the text is cut
+fn truncate(h: &Helper) -> Result<Value, RenderError> {
+    let text = string_param(h, 0, "text")?;
+    let length = usize_param(h, 1, "length")?;
+
+    if text.chars().count() <= length {
+        return Ok(Value::String(text));
+    }
+    let mut truncated: String = text.chars().take(length).collect();
+    truncated.push_str(&hash_str(h, "suffix").unwrap_or_default());
+
+    Ok(Value::String(truncated))
+}
+
+/// `{{replace name " " "_"}}`
+fn replace(h: &Helper) -> Result<Value, RenderError> {
+    let text = string_param(h, 0, "text")?;
+    let from = string_param(h, 1, "text to replace")?;
+    let to = string_param(h, 2, "replacement")?;
+
+    Ok(Value::String(text.replace(&from, &to)))
+}
+
+/// `{{regex_replace name "([0-9]+)-([0-9]+)" "$2-$1"}}`
+fn regex_replace(h: &Helper) -> Result<Value, RenderError> {
+    let text = string_param(h, 0, "text")?;
+    let pattern = string_param(h, 1, "pattern")?;
+    let replacement = string_param(h, 2, "replacement")?;
+
+    let regex = Regex::new(&pattern).map_err(|e| {
+        RenderError::new(format!(
+            "Helper regex_replace: invalid pattern {:?}: {}",
+            pattern, e
+        ))
+    })?;
+
+    Ok(Value::String(
+        regex.replace_all(&text, replacement.as_str()).to_string(),
+    ))
+}
+
+/// `{{default meta.author "Anonymous"}}`. Falls back when the value is missing or empty
+fn default(h: &Helper) -> Result<Value, RenderError> {
+    let value = h.param(0).map(|p| p.value().clone()).unwrap_or(Value::Null);
+    let is_empty = match &value {
+        Value::Null => true,
+        Value::String(s) => s.is_empty(),
+        Value::Array(a) => a.is_empty(),
+        _ => false,
+    };
+
+    if is_empty {
+        h.param(1)
+            .map(|p| p.value().clone())
+            .ok_or_else(|| RenderError::new("Helper default: missing fallback value"))
+    } else {
+        Ok(value)
+    }
+}
+
+/// `{{join tags ", "}}`
+fn join(h: &Helper) -> Result<Value, RenderError> {
+    let separator = h
+        .param(1)
+        .map(|p| to_string(p.value()))
+        .unwrap_or_else(|| ", ".to_string());
+
+    match h.param(0).map(|p| p.value()) {
+        Some(Value::Array(items)) => Ok(Value::String(
+            items
+                .iter()
+                .map(to_string)
+                .collect::<Vec<_>>()
+                .join(&separator),
+        )),
+        Some(Value::Null) | None => Ok(Value::String(String::new())),
+        Some(other) => Ok(Value::String(to_string(other))),
+    }
+}
+
+/// `{{#each (split meta.attendees ",")}}{{trim this}}{{/each}}`
+fn split(h: &Helper) -> Result<Value, RenderError> {
+    let text = string_param(h, 0, "text")?;
+    let separator = string_param(h, 1, "separator")?;
+    if text.is_empty() {
+        return Ok(Value::Array(vec![]));
+    }
+
+    Ok(Value::Array(
+        text.split(separator.as_str())
+            .map(|s| Value::String(s.to_string()))
+            .collect(),
+    ))
+}
+
+/// `{{trim name}}`
+fn trim(h: &Helper) -> Result<Value, RenderError> {
+    Ok(Value::String(
+        string_param(h, 0, "text")?.trim().to_string(),
+    ))
+}
+
+/// `{{pad now.month 2 char="0"}}`. Pads on the left unless `side="right"`
+fn pad(h: &Helper) -> Result<Value, RenderError> {
+    let text = string_param(h, 0, "text")?;
+    let width = usize_param(h, 1, "width")?;
+    let fill = hash_str(h, "char")
+        .and_then(|c| c.chars().next())
+        .unwrap_or(' ');
+    let padding: String =
+        std::iter::repeat_n(fill, width.saturating_sub(text.chars().count())).collect();
+
+    match hash_str(h, "side").as_deref() {
+        None | Some("left") => Ok(Value::String(padding + text.as_str())),
+        Some("right") => Ok(Value::String(text + padding.as_str())),
+        Some(side) => Err(RenderError::new(format!(
+            "Helper pad: unknown side {:?}, use left or right",
+            side
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn render(template: &str, data: &Value) -> Result<String, RenderError> {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_escape_fn(handlebars::no_escape);
+        register_string_helpers(&mut handlebars);
+        handlebars.render_template(template, data)
+    }
+
+    #[test]
+    fn can_change_case() {
+        let data = json!({"name": "Weekly Sync: Q1 / Planning"});
+
+        assert_eq!(
+            "weekly-sync-q1-planning",
+            render("{{slugify name}}", &data).unwrap()
+        );
+        assert_eq!(
+            "weekly sync: q1 / planning",
+            render("{{lower name}}", &data).unwrap()
+        );
+        assert_eq!(
+            "WEEKLY SYNC: Q1 / PLANNING",
+            render("{{upper name}}", &data).unwrap()
+        );
+        assert_eq!(
+            "Hello World-Wide",
+            render("{{title_case \"hello world-wide\"}}", &data).unwrap()
+        );
+    }
+
+    #[test]
+    fn can_edit_text() {
+        let data = json!({"name": "  meeting 2024-03  "});
+
+        assert_eq!("meeting 2024-03", render("{{trim name}}", &data).unwrap());
+        assert_eq!(
+            "meet...",
+            render("{{truncate (trim name) 4 suffix=\"...\"}}", &data).unwrap()
+        );
+        assert_eq!("short", render("{{truncate \"short\" 10}}", &data).unwrap());
+        assert_eq!(
+            "meeting_2024-03",
+            render("{{replace (trim name) \" \" \"_\"}}", &data).unwrap()
+        );
+        assert_eq!(
+            "meeting 03/2024",
+            render(
+                "{{regex_replace (trim name) \"([0-9]+)-([0-9]+)\" \"$2/$1\"}}",
+                &data
+            )
+            .unwrap()
+        );
+        assert!(render("{{regex_replace name \"(\" \"\"}}", &data)
+            .unwrap_err()
+            .to_string()
+            .contains("invalid pattern"));
+    }
+
+    #[test]
+    fn can_use_default_values() {
+        let data = json!({"meta": {"author": "", "who": "Max"}});
+
+        assert_eq!(
+            "Anonymous Max Anonymous",
+            render(
+                "{{default meta.author \"Anonymous\"}} {{default meta.who \"Anonymous\"}} {{default meta.missing \"Anonymous\"}}",
+                &data
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn can_split_and_join() {
+        let data = json!({"tags": ["a", "b"], "attendees": "Max, Anna"});
+
+        assert_eq!("a, b", render("{{join tags}}", &data).unwrap());
+        assert_eq!("a|b", render("{{join tags \"|\"}}", &data).unwrap());
+        assert_eq!(
+            "[Max][Anna]",
+            render(
+                "{{#each (split attendees \",\")}}[{{trim this}}]{{/each}}",
+                &data
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn can_pad() {
+        let data = json!({"month": 3});
+
+        assert_eq!("03", render("{{pad month 2 char=\"0\"}}", &data).unwrap());
+        assert_eq!(
+            "ab  |",
+            render("{{pad \"ab\" 4 side=\"right\"}}|", &data).unwrap()
+        );
+        assert_eq!("abc", render("{{pad \"abc\" 2}}", &data).unwrap());
+    }
+}
@@ -13,10 +13,7 @@ pub fn parse_metadata(meta: Option<ValuesRef<'_, String>>) -> HashMap<String, St
 pub fn parse_metadata_json(meta: Option<ValuesRef<'_, String>>) -> HashMap<String, String> {
     let maps: Vec<HashMap<String, String>> = meta
         .unwrap_or_default()
-        .filter_map(|v| {
-            let t = serde_json::from_str::<HashMap<String, String>>(v).ok();
-            t
-        })
+        .filter_map(|v| serde_json::from_str::<HashMap<String, String>>(v).ok())
         .collect();
 
     let mut result = HashMap::new();
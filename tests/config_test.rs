@@ -0,0 +1,98 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*;
+use std::{fs, process::Command};
+
+#[test]
+fn can_show_config_with_sources() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config-vaults"])
+        .args(["-m", "mood:happy", "-m", "who:Sam"])
+        .env("NOTES_CLI_STRICT_TEMPLATES", "true")
+        .args(["config", "show"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "default_vault = \"work\" # ./test-config-vaults/config.toml\n",
+        ))
+        .stdout(predicate::str::contains(
+            "note_file_type = \"md\" # default\n",
+        ))
+        .stdout(predicate::str::contains("strict_templates = true # env\n"))
+        .stdout(predicate::str::contains("team = \"core\" # vault work\n"))
+        .stdout(predicate::str::contains("mood = \"happy\" # flag\n"))
+        .stdout(predicate::str::contains(
+            "who = \"Max Hill\" # ./test-config-vaults/config.toml\n",
+        ));
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config-vaults"])
+        .args(["config", "show", "--json"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\"meta.team\": \"vault work\""));
+
+    Ok(())
+}
+
+#[test]
+fn can_get_config_values_and_paths() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config-vaults"]).args([
+        "--vault",
+        "personal",
+        "config",
+        "get",
+        "notes_dir",
+    ]);
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n",
+        std::env::current_dir()?
+            .join("test-config-vaults/personal-notes")
+            .display()
+    )));
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config-vaults"])
+        .args(["config", "get", "editor"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("editor is not set"));
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config-vaults"])
+        .args(["config", "path"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::diff("./test-config-vaults/config.toml\n"));
+
+    Ok(())
+}
+
+#[test]
+fn can_set_config_values() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = std::env::temp_dir().join(format!("notes-cli-config-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir)?;
+    fs::write(
+        dir.join("config.toml"),
+        "notes_dir = \"./notes\" # Where notes go\ntemplates_dir = \"./templates\"\n",
+    )?;
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.arg("--config-path")
+        .arg(&dir)
+        .args(["config", "set", "meta.author", "Max Hill"]);
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.arg("--config-path")
+        .arg(&dir)
+        .args(["config", "get", "meta.author"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::diff("Max Hill\n"));
+    assert!(fs::read_to_string(dir.join("config.toml"))?.contains("# Where notes go"));
+
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
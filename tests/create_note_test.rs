@@ -158,3 +158,258 @@ fn can_use_metadata_flat_in_template() -> Result<(), Box<dyn std::error::Error>>
 
     Ok(fs::remove_file(&note_path)?)
 }
+
+#[test]
+fn fails_if_note_already_exists() -> Result<(), Box<dyn std::error::Error>> {
+    let name = unique_file_name("test_name", "md");
+    let note_path = setup_notes_dir()?.join(&name);
+    fs::write(&note_path, "")?;
+
+    for name in [name.clone(), name.to_uppercase()] {
+        let mut cmd = Command::cargo_bin("notes-cli")?;
+        cmd.args(["--config-path", "./test-config"])
+            .arg("new")
+            .arg(&name);
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains("already exists"));
+    }
+
+    Ok(fs::remove_file(&note_path)?)
+}
+
+#[test]
+fn can_use_structured_now_in_name_template() -> Result<(), Box<dyn std::error::Error>> {
+    let name = unique_file_name("test_name", "md");
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config"])
+        .arg("new")
+        .arg(&name)
+        .args(["--name-template", "{{now.year}}-W{{now.iso_week}}_{{name}}"]);
+
+    let output = cmd
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"/\d{4}-W\d{1,2}_test_name-")?)
+        .get_output()
+        .stdout
+        .clone();
+
+    Ok(fs::remove_file(String::from_utf8(output)?.trim())?)
+}
+
+#[test]
+fn can_set_the_reference_date() -> Result<(), Box<dyn std::error::Error>> {
+    let name = unique_file_name("test_name", "md");
+    let expected_output_filename = setup_notes_dir()?.join(format!("2024-03-01_{}", name));
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config"])
+        .arg("new")
+        .arg(&name)
+        .args(["--date", "2024-03-01"])
+        .args(["--name-template", "{{date now}}_{{name}}"]);
+
+    cmd.assert().success().stdout(predicate::str::contains(
+        expected_output_filename.to_string_lossy(),
+    ));
+
+    Ok(fs::remove_file(expected_output_filename)?)
+}
+
+#[test]
+fn fails_on_invalid_timezone() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config"])
+        .env("NOTES_CLI_TIMEZONE", "Mars/Olympus")
+        .arg("new")
+        .arg(unique_file_name("test_name", "md"));
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Invalid timezone \"Mars/Olympus\"",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn rejects_numbers_as_reference_date() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config"])
+        .arg("new")
+        .arg(unique_file_name("test_name", "md"))
+        .args(["--date", "5"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Could not understand date \"5\""));
+
+    Ok(())
+}
+
+#[test]
+fn can_use_string_helpers_in_name_template() -> Result<(), Box<dyn std::error::Error>> {
+    let id = Uuid::new_v4();
+    let expected_output_filename = setup_notes_dir()?.join(format!("weekly-sync-{}.md", id));
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config"])
+        .arg("new")
+        .arg(format!("Weekly Sync: {}", id))
+        .args(["--name-template", "{{slugify name}}"]);
+
+    cmd.assert().success().stdout(predicate::str::contains(
+        expected_output_filename.to_string_lossy(),
+    ));
+
+    Ok(fs::remove_file(expected_output_filename)?)
+}
+
+#[test]
+fn can_use_nested_templates_and_partials() -> Result<(), Box<dyn std::error::Error>> {
+    let name = unique_file_name("test_name", "md");
+    let note_path = setup_notes_dir()?.join(&name);
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config"])
+        .arg("new")
+        .arg(&name)
+        .args(["--template", "work/meeting"]);
+
+    cmd.assert().success();
+
+    let contents = fs::read_to_string(&note_path)
+        .with_context(|| format!("Could not: read file {:?}", &note_path))
+        .unwrap();
+    assert!(predicate::str::contains("Author: Max Hill").eval(&contents));
+    assert!(predicate::str::contains("# Meeting: test_name").eval(&contents));
+
+    Ok(fs::remove_file(&note_path)?)
+}
+
+#[test]
+fn can_use_layouts() -> Result<(), Box<dyn std::error::Error>> {
+    let name = unique_file_name("test_name", "md");
+    let note_path = setup_notes_dir()?.join(&name);
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config"])
+        .arg("new")
+        .arg(&name)
+        .args(["--template", "test-layout"])
+        .args(["--date", "2024-03-01"]);
+
+    cmd.assert().success();
+
+    let contents = fs::read_to_string(&note_path)
+        .with_context(|| format!("Could not: read file {:?}", &note_path))
+        .unwrap();
+    assert!(predicate::str::starts_with("---\ntitle: Layout test_name").eval(&contents));
+    assert!(predicate::str::contains("author: Max Hill\n---\n").eval(&contents));
+    assert!(predicate::str::contains("Author: Max Hill").eval(&contents));
+    assert!(predicate::str::contains("Body of test_name").eval(&contents));
+    assert!(predicate::str::ends_with("Created 2024-03-01\n").eval(&contents));
+
+    Ok(fs::remove_file(&note_path)?)
+}
+
+#[test]
+fn can_use_script_helpers() -> Result<(), Box<dyn std::error::Error>> {
+    let name = unique_file_name("test_name", "md");
+    let expected_output_filename = setup_notes_dir()?.join(format!("MH_{}", name));
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config"])
+        .arg("new")
+        .arg(&name)
+        .args(["--name-template", "{{initials config.meta.who}}_{{name}}"]);
+
+    cmd.assert().success().stdout(predicate::str::contains(
+        expected_output_filename.to_string_lossy(),
+    ));
+
+    Ok(fs::remove_file(expected_output_filename)?)
+}
+
+#[test]
+fn strict_mode_fails_on_missing_variables() -> Result<(), Box<dyn std::error::Error>> {
+    let name = unique_file_name("test_name", "md");
+    let expected_output_filename = setup_notes_dir()?.join(&name);
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config"])
+        .arg("new")
+        .arg(&name)
+        .args(["--template", "test-strict"])
+        .arg("--strict");
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Variable \"meta.atuhor\" not found in strict mode",
+    ));
+    assert!(!expected_output_filename.exists());
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config"])
+        .arg("new")
+        .arg(&name)
+        .args(["--name-template", "{{nmae}}"])
+        .arg("--strict");
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Variable \"nmae\" not found in strict mode",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn can_use_built_in_templates() -> Result<(), Box<dyn std::error::Error>> {
+    let name = unique_file_name("test_name", "md");
+    let expected_output_filename = setup_notes_dir()?.join(&name);
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config"])
+        .arg("new")
+        .arg(&name)
+        .args(["--template", "meeting", "--strict"]);
+
+    cmd.assert().success();
+    let contents = fs::read_to_string(&expected_output_filename)?;
+    assert!(contents.starts_with("---\ntitle: "));
+    assert!(contents.contains("## Action items"));
+
+    Ok(fs::remove_file(expected_output_filename)?)
+}
+
+#[test]
+fn vault_config_can_not_allow_exec() -> Result<(), Box<dyn std::error::Error>> {
+    let vault = std::env::temp_dir().join(format!("notes-cli-vault-{}", Uuid::new_v4()));
+    fs::create_dir_all(vault.join(".templates"))?;
+    fs::write(
+        vault.join(".notes-cli.toml"),
+        "notes_dir = \".\"\ntemplates_dir = \".templates\"\nallow_exec = true\nexec_allow_list = [\"sh\"]\n",
+    )?;
+    fs::write(
+        vault.join(".templates/daily.hbs"),
+        "{{exec \"sh\" \"-c\" \"touch pwned\"}}",
+    )?;
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.current_dir(&vault)
+        .env("XDG_CONFIG_HOME", vault.join("no-user-config"))
+        .args(["new", "x", "--template", "daily"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "allow_exec is ignored, it can only be set in config.toml",
+        ))
+        .stderr(predicate::str::contains(
+            "Set allow_exec = true in config.toml",
+        ));
+    assert!(!vault.join("pwned").exists());
+
+    fs::remove_dir_all(vault)?;
+    Ok(())
+}
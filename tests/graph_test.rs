@@ -0,0 +1,60 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*;
+use std::process::Command;
+
+#[test]
+fn can_export_graph_as_dot() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config-graph"])
+        .arg("graph");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("digraph notes {"))
+        .stdout(predicate::str::contains(
+            "\"index\" -> \"projects/roadmap\";",
+        ))
+        .stdout(predicate::str::contains("\"index\" -> \"team\";"))
+        .stdout(predicate::str::contains(
+            "\"projects/roadmap\" -> \"goals\" [style=dashed, label=\"embed\"];",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn can_export_subgraph_as_json() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config-graph"])
+        .arg("graph")
+        .args(["--format", "json", "--note", "goals", "--depth", "1"]);
+
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let graph: serde_json::Value = serde_json::from_slice(&output)?;
+    let ids: Vec<_> = graph["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|n| n["id"].as_str().unwrap())
+        .collect();
+
+    assert_eq!(vec!["goals", "projects/roadmap"], ids);
+    assert_eq!("embed", graph["edges"][0]["kind"]);
+
+    Ok(())
+}
+
+#[test]
+fn can_export_tag_edges_as_mermaid() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config-graph"])
+        .arg("graph")
+        .args(["--format", "mermaid", "--tag-edges"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with("graph LR"))
+        .stdout(predicate::str::contains("---|#planning|"));
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*;
+use std::{fs, process::Command};
+
+#[test]
+fn can_init_with_flags() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = std::env::temp_dir().join(format!("notes-cli-init-{}", uuid::Uuid::new_v4()));
+    let config_dir = dir.join("config");
+    let notes_dir = dir.join("notes");
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.arg("--config-path")
+        .arg(&config_dir)
+        .args(["init", "--yes", "--notes-dir"])
+        .arg(&notes_dir);
+
+    cmd.assert().success();
+    assert!(notes_dir.is_dir());
+    assert!(config_dir.join("templates/meeting.hbs").is_file());
+    assert!(fs::read_to_string(config_dir.join("config.toml"))?
+        .contains(&format!("notes_dir = {:?}", notes_dir)));
+
+    // The new config works
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.arg("--config-path")
+        .arg(&config_dir)
+        .args(["templates", "list"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("meeting   template  user"));
+
+    // Existing files are kept unless forced
+    fs::write(config_dir.join("config.toml"), "# mine")?;
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.arg("--config-path")
+        .arg(&config_dir)
+        .args(["init", "--yes"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("config.toml\", it already exists"));
+    assert_eq!(
+        "# mine",
+        fs::read_to_string(config_dir.join("config.toml"))?
+    );
+
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[test]
+fn can_init_interactively() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = std::env::temp_dir().join(format!("notes-cli-init-{}", uuid::Uuid::new_v4()));
+    let config_dir = dir.join("config");
+
+    let mut cmd = assert_cmd::Command::cargo_bin("notes-cli")?;
+    cmd.arg("--config-path")
+        .arg(&config_dir)
+        .arg("init")
+        .env("HOME", &dir)
+        .current_dir(std::env::temp_dir())
+        .write_stdin(format!("~/notes\n{}\nn\n", dir.join("templates").display()));
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Notes directory ["))
+        .stdout(predicate::str::contains("Add starter templates? [Y/n]"));
+    assert!(dir.join("notes").is_dir());
+    assert!(dir.join("templates").is_dir());
+    assert!(!dir.join("templates/meeting.hbs").exists());
+    let config = fs::read_to_string(config_dir.join("config.toml"))?;
+    assert!(config.contains(&format!("notes_dir = {:?}", dir.join("notes"))));
+    assert!(config.contains(&format!("templates_dir = {:?}", dir.join("templates"))));
+
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
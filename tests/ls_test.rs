@@ -13,3 +13,28 @@ fn can_list_notes() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn can_read_config_from_vault_and_env() -> Result<(), Box<dyn std::error::Error>> {
+    let fixture = std::env::current_dir()?.join("test-config-ls");
+    let vault = std::env::temp_dir().join(format!("notes-cli-vault-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(vault.join("projects"))?;
+    std::fs::write(
+        vault.join(".notes-cli.toml"),
+        "notes_dir = \"/does-not-exist\"\n[subcommands]\n[meta]\n",
+    )?;
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.current_dir(vault.join("projects"))
+        .env("XDG_CONFIG_HOME", vault.join("no-user-config"))
+        .env("NOTES_CLI_NOTES_DIR", fixture.join("notes-dir"))
+        .env("NOTES_CLI_TEMPLATES_DIR", fixture.join("templates"))
+        .arg("ls");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("test-note.md"));
+
+    std::fs::remove_dir_all(vault)?;
+    Ok(())
+}
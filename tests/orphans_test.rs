@@ -0,0 +1,42 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*;
+use std::process::Command;
+
+#[test]
+fn can_list_orphans() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config-graph"])
+        .arg("orphans");
+
+    cmd.assert().success().stdout(predicate::str::diff(
+        "No inbound links:
+  ideas/one
+  index
+  scratch
+No outbound links:
+  goals
+  ideas/two
+  scratch
+  team
+Isolated clusters:
+  ideas/one, ideas/two
+",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn can_exclude_folders_from_orphans() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config-graph"])
+        .arg("orphans")
+        .args(["--exclude", "ideas"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("ideas/").not())
+        .stdout(predicate::str::contains("daily/").not());
+
+    Ok(())
+}
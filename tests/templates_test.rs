@@ -0,0 +1,143 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*;
+use std::{fs, process::Command};
+
+#[test]
+fn can_list_templates() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config"])
+        .args(["templates", "list"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "work/meeting   template  user      Meeting notes\n",
+        ))
+        .stdout(predicate::str::contains("header         partial   user\n"))
+        .stdout(predicate::str::contains("layouts/base   layout    user\n"));
+
+    Ok(())
+}
+
+#[test]
+fn can_show_template() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config"])
+        .args(["templates", "show", "work/meeting"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("# Meeting: {{name}}"));
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config"])
+        .args(["templates", "show", "does-not-exist"]);
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Could not find template does-not-exist",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn can_check_templates() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config-templates"])
+        .args(["templates", "check"]);
+
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::diff(
+            "broken: error at line 4, column 1: invalid handlebars syntax.
+daily: ok
+  variables: meta.todo, now
+",
+        ))
+        .stderr(predicate::str::contains("1 of 2 templates have errors"));
+
+    Ok(())
+}
+
+#[test]
+fn earlier_template_layers_override_later_ones() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config-layers"])
+        .args(["templates", "list"]);
+
+    cmd.assert().success().stdout(predicate::str::diff(
+        "daily      template  user      Daily log
+decision   template  embedded  Decision record: context, decision and consequences
+empty      template  embedded
+meeting    template  vault     Meeting notes for this vault
+project    template  embedded  Project overview with goal, milestones and tasks
+retro      template  team      Sprint retrospective
+signature  partial   team
+weekly     template  embedded  Weekly review
+zettel     template  embedded  Zettelkasten note with a timestamp id
+",
+    ));
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config-layers"])
+        .args(["templates", "show", "meeting"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("{{> signature}}"));
+
+    Ok(())
+}
+
+#[test]
+fn can_export_starter_templates() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = std::env::temp_dir().join(format!("notes-cli-export-{}", uuid::Uuid::new_v4()));
+    let templates_dir = dir.join("templates");
+    fs::create_dir_all(&templates_dir)?;
+    fs::write(
+        dir.join("config.toml"),
+        format!(
+            "notes_dir = {:?}\ntemplates_dir = {:?}\n[subcommands]\n[meta]\n",
+            dir, templates_dir
+        ),
+    )?;
+    fs::write(templates_dir.join("meeting.hbs"), "My meeting")?;
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", dir.to_str().unwrap()]).args([
+        "templates",
+        "export",
+        "daily",
+        "meeting",
+    ]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("daily.hbs"))
+        .stdout(predicate::str::contains("Skipped"));
+    assert!(fs::read_to_string(templates_dir.join("daily.hbs"))?.contains("Daily journal"));
+    assert_eq!(
+        "My meeting",
+        fs::read_to_string(templates_dir.join("meeting.hbs"))?
+    );
+    assert!(!templates_dir.join("weekly.hbs").exists());
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", dir.to_str().unwrap()])
+        .args(["templates", "export", "--force"]);
+
+    cmd.assert().success();
+    assert!(fs::read_to_string(templates_dir.join("meeting.hbs"))?.contains("Action items"));
+    assert!(templates_dir.join("weekly.hbs").exists());
+    assert!(!templates_dir.join("empty.hbs").exists());
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", dir.to_str().unwrap()])
+        .args(["templates", "export", "standup"]);
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "There is no built-in template standup",
+    ));
+
+    Ok(fs::remove_dir_all(dir)?)
+}
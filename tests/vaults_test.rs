@@ -0,0 +1,53 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*;
+use std::process::Command;
+
+#[test]
+fn can_list_vaults() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config-vaults"])
+        .args(["vaults", "list"]);
+
+    let root = std::env::current_dir()?;
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "  inbox     {}\n  personal  {}\n* work      {}\n",
+        root.join("test-config-vaults/notes-dir").display(),
+        root.join("test-config-vaults/personal-notes").display(),
+        root.join("test-config-ls/notes-dir").display()
+    )));
+
+    Ok(())
+}
+
+#[test]
+fn can_select_a_vault() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config-vaults"])
+        .arg("ls");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("test-note.md"));
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config-vaults"])
+        .args(["--vault", "personal", "ls"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("garden.md"));
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config-vaults"])
+        .args(["--vault", "personal", "where"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("personal Max Hill"));
+
+    let mut cmd = Command::cargo_bin("notes-cli")?;
+    cmd.args(["--config-path", "./test-config-vaults"])
+        .args(["--vault", "garden", "ls"]);
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Could not find vault garden. Available: inbox, personal, work",
+    ));
+
+    Ok(())
+}